@@ -0,0 +1,50 @@
+//! Plays seeded games headlessly and prints the bot results
+//!
+//! `cargo run -p board_plugin --bin solver_bot -- --width 30 --height 16 --bombs 99 --games 1000 --seed 7`
+
+use std::env;
+use std::process;
+use board_plugin::resources::BoardOptions;
+use board_plugin::solver::play_games;
+
+const USAGE: &str = "usage: solver_bot [--width N] [--height N] [--bombs N] [--games N] [--seed N] [--safe-start]";
+
+fn main() {
+    let mut options = BoardOptions::default();
+    let mut games = 100;
+    let mut seed = 0;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--safe-start" {
+            options.safe_start = true;
+            continue;
+        }
+        let value = match args.next().map(|v| v.parse::<u64>()) {
+            Some(Ok(v)) => v,
+            _ => exit_with_usage(&arg),
+        };
+        match arg.as_str() {
+            "--width" => options.map_size.0 = value as u16,
+            "--height" => options.map_size.1 = value as u16,
+            "--bombs" => options.bomb_count = value as u16,
+            "--games" => games = value as u32,
+            "--seed" => seed = value,
+            _ => exit_with_usage(&arg),
+        }
+    }
+
+    let report = play_games(&options, seed, games);
+    println!(
+        "{} games on ({}, {}) with {} bombs, seed {}",
+        report.games, options.map_size.0, options.map_size.1, options.bomb_count, seed
+    );
+    println!("win rate: {:.1}%", report.win_rate() * 100.);
+    println!("guesses per game: {:.2}", report.guesses_per_game());
+    println!("time: {:.2?}", report.elapsed);
+}
+
+fn exit_with_usage(arg: &str) -> ! {
+    eprintln!("invalid argument {}\n{}", arg, USAGE);
+    process::exit(2);
+}
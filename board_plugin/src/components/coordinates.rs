@@ -21,13 +21,24 @@ impl Add for Coordinates {
     }
 }
 
-impl Add<(i8, i8)> for Coordinates {
-    type Output = Self;
+impl Coordinates {
+    /// Coordinates moved by a delta, `None` if they'd be negative
+    pub fn checked_add(self, (x, y): (i8, i8)) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add_signed(x.into())?,
+            y: self.y.checked_add_signed(y.into())?,
+        })
+    }
 
-    fn add(self, (x, y): (i8, i8)) -> Self::Output {
-        let x = ((self.x as i16) + x as i16) as u16;
-        let y = ((self.y as i16) + y as i16) as u16;
-        Self { x, y }
+    /// Coordinates moved by a delta on a `width` x `height` grid whose edges wrap around
+    pub fn wrapping_add(self, (x, y): (i8, i8), (width, height): (u16, u16)) -> Self {
+        let wrap = |value: u16, delta: i8, size: u16| {
+            (value as i32 + delta as i32).rem_euclid(size as i32) as u16
+        };
+        Self {
+            x: wrap(self.x, x, width),
+            y: wrap(self.y, y, height),
+        }
     }
 }
 
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Flags of a bomb uncovered at the cost of a life, spawned on its tile
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Detonated;
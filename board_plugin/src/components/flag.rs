@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Flag sprite of a marked cover, or of a bomb claimed or uncovered at the cost of a life
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Flag;
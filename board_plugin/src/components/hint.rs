@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Highlight of a hinted tile or of a tile proving the hint
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct HintHighlight;
@@ -1,9 +1,23 @@
 pub use bomb::Bomb;
 pub use bomb_neighbor::BombNeighbor;
 pub use coordinates::Coordinates;
+pub use detonated::Detonated;
+pub use flag::Flag;
+pub use hint::HintHighlight;
+pub use probability::Probability;
+pub use themed::Themed;
+pub use tween::{Tween, TweenKind};
 pub use uncover::Uncover;
+pub use world_coordinates::{ChunkCoordinates, WorldCoordinates};
 
 mod bomb;
 mod bomb_neighbor;
 mod coordinates;
+mod detonated;
+mod flag;
+mod hint;
+mod probability;
+mod themed;
+mod tween;
 mod uncover;
+mod world_coordinates;
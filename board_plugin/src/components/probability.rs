@@ -0,0 +1,11 @@
+use bevy::prelude::Component;
+
+/// Probability overlay shown on top of a tile cover
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, PartialEq, Component)]
+pub struct Probability {
+    /// Mine probability of the covered tile
+    pub value: f32,
+    /// Is the value a density estimate, the board being too open to solve exactly
+    pub approximate: bool,
+}
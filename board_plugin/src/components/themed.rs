@@ -0,0 +1,19 @@
+use bevy::prelude::Component;
+use crate::resources::Player;
+
+/// Part of the board styled from the `BoardAssets`, restyled when they change
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub enum Themed {
+    Background,
+    Tile,
+    Cover,
+    Flag,
+    /// Flag of a bomb claimed by a player of a versus game
+    PlayerFlag(Player),
+    Bomb,
+    /// Bomb counter text, colored from its tile's `BombNeighbor`
+    Counter,
+    /// Secondary cue of a bomb counter, see `NumberCue`
+    Cue,
+}
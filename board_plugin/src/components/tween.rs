@@ -0,0 +1,51 @@
+use std::time::Duration;
+use bevy::prelude::{Component, Vec3};
+
+/// Animated property of a `Tween`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TweenKind {
+    /// Fades the sprite out from `alpha` then despawns the entity
+    FadeOut { alpha: f32 },
+    /// Shrinks the sprite horizontally then despawns the entity
+    Flip,
+    /// Falls from `height` above `rest` while shrinking to its normal size
+    DropIn { rest: Vec3, height: f32 },
+    /// Shakes around `origin` with a decaying amplitude, then settles back on it
+    Shake { origin: Vec3, amplitude: f32 },
+}
+
+/// Animation of an entity, driven by `systems::animation::animate`
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Tween {
+    pub kind: TweenKind,
+    /// Seconds before the animation starts
+    pub delay: f32,
+    /// Seconds the animation lasts
+    pub duration: f32,
+    /// Seconds since the tween was added, delay included
+    pub elapsed: f32,
+}
+
+impl Tween {
+    pub fn new(kind: TweenKind, duration: Duration) -> Self {
+        Self {
+            kind,
+            delay: 0.,
+            duration: duration.as_secs_f32(),
+            elapsed: 0.,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay.as_secs_f32();
+        self
+    }
+
+    /// Animation progress from 0 to 1, 0 during the delay
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0. {
+            return if self.elapsed >= self.delay { 1. } else { 0. };
+        }
+        ((self.elapsed - self.delay) / self.duration).clamp(0., 1.)
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt::{self, Display, Formatter};
+use bevy::prelude::Component;
+use crate::resources::NeighborhoodRule;
+
+/// Coordinates of a tile of the endless board, which extends in every direction
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct WorldCoordinates {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl WorldCoordinates {
+    /// The 8 tiles around
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        NeighborhoodRule::Adjacent.deltas().iter().map(move |(x, y)| Self {
+            x: self.x + *x as i32,
+            y: self.y + *y as i32,
+        })
+    }
+
+    /// Chunk holding the tile
+    pub fn chunk(self) -> ChunkCoordinates {
+        ChunkCoordinates {
+            x: self.x.div_euclid(ChunkCoordinates::SIZE),
+            y: self.y.div_euclid(ChunkCoordinates::SIZE),
+        }
+    }
+
+    /// Index of the tile in its chunk, row by row
+    pub fn index_in_chunk(self) -> usize {
+        let size = ChunkCoordinates::SIZE;
+        (self.y.rem_euclid(size) * size + self.x.rem_euclid(size)) as usize
+    }
+}
+
+impl Display for WorldCoordinates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Coordinates of a square chunk of the endless board, counted in chunks
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct ChunkCoordinates {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ChunkCoordinates {
+    /// Width and height of a chunk, in tiles
+    pub const SIZE: i32 = 16;
+    /// Number of tiles in a chunk
+    pub const TILES: usize = (Self::SIZE * Self::SIZE) as usize;
+
+    /// Tile at `index` in the chunk, see `WorldCoordinates::index_in_chunk`
+    pub fn tile(self, index: usize) -> WorldCoordinates {
+        let index = index as i32;
+        WorldCoordinates {
+            x: self.x * Self::SIZE + index % Self::SIZE,
+            y: self.y * Self::SIZE + index / Self::SIZE,
+        }
+    }
+
+    /// All the tiles of the chunk, row by row
+    pub fn tiles(self) -> impl Iterator<Item = WorldCoordinates> {
+        (0..Self::TILES).map(move |i| self.tile(i))
+    }
+}
+
+impl Display for ChunkCoordinates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
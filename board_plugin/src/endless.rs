@@ -0,0 +1,374 @@
+use bevy::ecs::schedule::StateData;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ButtonState;
+use bevy::log;
+use bevy::prelude::*;
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::RegisterInspectable;
+use rand::Rng;
+use crate::components::{Bomb, BombNeighbor, ChunkCoordinates, Themed, WorldCoordinates};
+use crate::events::{EndlessGameOverEvent, EndlessMarkEvent, EndlessTriggerEvent};
+use crate::resources::{BoardAssets, EndlessBoard, EndlessOptions, NumberStyle, Stats, Tile, Topology};
+use crate::{systems, BoardPlugin};
+
+/// Speed of the camera moved with the arrow keys, in pixels per second
+const PAN_SPEED: f32 = 600.;
+
+/// Endless mode: an unbounded board streamed in chunks around the camera.
+/// The app provides the 2D camera, which this plugin moves
+pub struct EndlessPlugin<T> {
+    pub running_state: T,
+}
+
+impl<T: StateData> Plugin for EndlessPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(
+                SystemSet::on_enter(self.running_state.clone())
+                    .with_system(Self::create_board),
+            )
+            .add_system_set(
+                SystemSet::on_update(self.running_state.clone())
+                    .with_system(Self::input_handling)
+                    .with_system(Self::play.after(Self::input_handling))
+                    .with_system(Self::stream_chunks.after(Self::play))
+                    .with_system(systems::theme::apply_board_assets),
+            )
+            .add_system_set(
+                SystemSet::on_exit(self.running_state.clone())
+                    .with_system(Self::cleanup_board),
+            )
+            .add_event::<EndlessTriggerEvent>()
+            .add_event::<EndlessMarkEvent>()
+            .add_event::<EndlessGameOverEvent>()
+            .init_resource::<NumberStyle>()
+            .init_resource::<Stats>();
+        log::info!("Loaded EndlessPlugin");
+
+        #[cfg(feature = "debug")]
+        {
+            app.register_inspectable::<WorldCoordinates>();
+            app.register_inspectable::<ChunkCoordinates>();
+        }
+    }
+}
+
+impl<T> EndlessPlugin<T> {
+    /// Starts the endless game, resuming the saved one if any
+    pub fn create_board(mut commands: Commands, options: Option<Res<EndlessOptions>>) {
+        let options = match options {
+            None => EndlessOptions::default(),
+            Some(o) => o.clone(),
+        };
+        let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut board = match &options.save_dir {
+            None => EndlessBoard::new(seed, options.mines_per_chunk, None),
+            Some(dir) => match EndlessBoard::load(dir.clone(), seed, options.mines_per_chunk) {
+                Ok(board) => board,
+                Err(e) => {
+                    // The save is left untouched for another try
+                    log::error!("Failed to resume the endless game from {}: {}", dir.display(), e);
+                    EndlessBoard::new(seed, options.mines_per_chunk, None)
+                }
+            },
+        };
+        board.tile_size = options.tile_size;
+        log::info!("Endless game with seed {}, {} tiles cleared", board.seed, board.cleared);
+        commands.insert_resource(board);
+    }
+
+    /// Pans the camera with the arrow keys and turns clicks into endless board events
+    #[allow(clippy::too_many_arguments)]
+    pub fn input_handling(
+        windows: Res<Windows>,
+        time: Res<Time>,
+        keys: Res<Input<KeyCode>>,
+        board: Res<EndlessBoard>,
+        mut cameras: Query<&mut Transform, With<Camera2d>>,
+        mut button_evr: EventReader<MouseButtonInput>,
+        mut trigger_ewr: EventWriter<EndlessTriggerEvent>,
+        mut mark_ewr: EventWriter<EndlessMarkEvent>,
+    ) {
+        let mut direction = Vec2::ZERO;
+        for (key, delta) in [
+            (KeyCode::Left, Vec2::NEG_X),
+            (KeyCode::Right, Vec2::X),
+            (KeyCode::Down, Vec2::NEG_Y),
+            (KeyCode::Up, Vec2::Y),
+        ] {
+            if keys.pressed(key) {
+                direction += delta;
+            }
+        }
+        for mut transform in cameras.iter_mut() {
+            transform.translation += (direction * PAN_SPEED * time.delta_seconds()).extend(0.);
+        }
+
+        let window = match windows.get_primary() {
+            Some(w) => w,
+            // Nothing to click on when running headless
+            None => return,
+        };
+        let camera = cameras.iter().next().map(|t| t.translation.truncate()).unwrap_or_default();
+        for event in button_evr.iter() {
+            if event.state != ButtonState::Pressed {
+                continue;
+            }
+            let position = match window.cursor_position() {
+                Some(p) => p,
+                None => continue,
+            };
+            // Window to world space, the camera isn't scaled
+            let position = position - Vec2::new(window.width(), window.height()) / 2. + camera;
+            let coordinates = WorldCoordinates {
+                x: (position.x / board.tile_size).floor() as i32,
+                y: (position.y / board.tile_size).floor() as i32,
+            };
+            match event.button {
+                MouseButton::Left => trigger_ewr.send(EndlessTriggerEvent(coordinates)),
+                MouseButton::Right => mark_ewr.send(EndlessMarkEvent(coordinates)),
+                _ => (),
+            }
+        }
+    }
+
+    /// Reveals and flags tiles, ending the game on a bomb
+    #[allow(clippy::too_many_arguments)]
+    pub fn play(
+        mut commands: Commands,
+        mut board: ResMut<EndlessBoard>,
+        board_assets: Res<BoardAssets>,
+        mut stats: ResMut<Stats>,
+        mut trigger_evr: EventReader<EndlessTriggerEvent>,
+        mut mark_evr: EventReader<EndlessMarkEvent>,
+        mut game_over_ewr: EventWriter<EndlessGameOverEvent>,
+    ) {
+        let mut changed = false;
+        for EndlessTriggerEvent(coords) in trigger_evr.iter() {
+            for tile in board.reveal(*coords) {
+                changed = true;
+                if let Some(cover) = board.covers.remove(&tile) {
+                    commands.entity(cover).despawn_recursive();
+                }
+            }
+        }
+        for EndlessMarkEvent(coords) in mark_evr.iter() {
+            if let Some(flagged) = board.toggle_flag(*coords) {
+                changed = true;
+                if let Some(cover) = board.covers.get(coords) {
+                    let tile_size = board.tile_size;
+                    systems::mark::set_flags(&mut commands, *cover, flagged as usize, &board_assets, tile_size, None);
+                }
+            }
+        }
+        if !changed {
+            return;
+        }
+        let result = match board.exploded {
+            None => board.save(),
+            Some(bomb) => {
+                log::info!("Boom at {}! {} tiles cleared", bomb, board.cleared);
+                game_over_ewr.send(EndlessGameOverEvent { cleared: board.cleared });
+                if stats.record_endless(board.cleared) {
+                    log::info!("New endless best: {} tiles", board.cleared);
+                }
+                if let Err(e) = stats.save() {
+                    log::error!("Failed to save stats: {}", e);
+                }
+                board.delete_save()
+            }
+        };
+        if let Err(e) = result {
+            log::error!("Failed to save the endless game: {}", e);
+        }
+    }
+
+    /// Spawns the chunks around the camera and despawns the ones left behind
+    pub fn stream_chunks(
+        mut commands: Commands,
+        mut board: ResMut<EndlessBoard>,
+        options: Option<Res<EndlessOptions>>,
+        board_assets: Res<BoardAssets>,
+        number_style: Res<NumberStyle>,
+        // `Windows` is missing when running headless (e.g. with `MinimalPlugins`)
+        windows: Option<Res<Windows>>,
+        cameras: Query<&Transform, With<Camera2d>>,
+    ) {
+        let options = match options {
+            None => EndlessOptions::default(),
+            Some(o) => o.clone(),
+        };
+        let center = cameras.iter().next().map(|t| t.translation.truncate()).unwrap_or_default();
+        let view = windows
+            .as_ref()
+            .and_then(|w| w.get_primary())
+            .map(|w| Vec2::new(w.width(), w.height()))
+            .unwrap_or(options.view_size);
+        let chunk_size = ChunkCoordinates::SIZE as f32 * board.tile_size;
+        let min = ((center - view / 2.) / chunk_size).floor();
+        let max = ((center + view / 2.) / chunk_size).floor();
+        let (xs, ys) = (
+            min.x as i32 - options.margin..=max.x as i32 + options.margin,
+            min.y as i32 - options.margin..=max.y as i32 + options.margin,
+        );
+
+        let behind: Vec<ChunkCoordinates> = board
+            .spawned_chunks
+            .keys()
+            .filter(|c| !xs.contains(&c.x) || !ys.contains(&c.y))
+            .copied()
+            .collect();
+        // The chunks left behind are dropped once saved
+        let evict = !behind.is_empty();
+        for chunk in behind {
+            if let Some(entity) = board.spawned_chunks.remove(&chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+            for tile in chunk.tiles() {
+                board.covers.remove(&tile);
+            }
+        }
+        if evict {
+            board.evict_chunks();
+        }
+        let mut spawned = false;
+        for y in ys {
+            for x in xs.clone() {
+                let chunk = ChunkCoordinates { x, y };
+                if !board.spawned_chunks.contains_key(&chunk) {
+                    Self::spawn_chunk(&mut commands, &mut board, chunk, &options, &board_assets, &number_style);
+                    spawned = true;
+                }
+            }
+        }
+        if !spawned {
+            return;
+        }
+        // Cascades which stopped at the chunks just spawned go on
+        let revealed = board.continue_cascades();
+        for tile in &revealed {
+            if let Some(cover) = board.covers.remove(tile) {
+                commands.entity(cover).despawn_recursive();
+            }
+        }
+        if !revealed.is_empty() {
+            if let Err(e) = board.save() {
+                log::error!("Failed to save the endless game: {}", e);
+            }
+        }
+    }
+
+    fn spawn_chunk(
+        commands: &mut Commands,
+        board: &mut EndlessBoard,
+        chunk: ChunkCoordinates,
+        options: &EndlessOptions,
+        board_assets: &BoardAssets,
+        number_style: &NumberStyle,
+    ) {
+        let size = board.tile_size;
+        let padded = size - options.tile_padding;
+        let tiles: Vec<(WorldCoordinates, Tile, bool, bool)> = chunk
+            .tiles()
+            .map(|c| (c, board.tile(c), board.is_revealed(c), board.is_flagged(c)))
+            .collect();
+        let mut covers = Vec::new();
+        let origin = Vec2::new(chunk.x as f32, chunk.y as f32) * ChunkCoordinates::SIZE as f32 * size;
+        let entity = commands
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(origin.extend(0.))))
+            .insert(Name::new(format!("Chunk {}", chunk)))
+            .insert(chunk)
+            .with_children(|parent| {
+                for (coords, tile, revealed, flagged) in tiles {
+                    let local = Vec2::new(
+                        (coords.x - chunk.x * ChunkCoordinates::SIZE) as f32,
+                        (coords.y - chunk.y * ChunkCoordinates::SIZE) as f32,
+                    );
+                    let mut cmd = parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: board_assets.tile_material.color,
+                            custom_size: Some(Vec2::splat(padded)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation((local * size + size / 2.).extend(1.)),
+                        texture: board_assets.tile_texture(&board_assets.tile_material, Topology::Square),
+                        ..Default::default()
+                    });
+                    cmd.insert(Name::new(format!("Tile {}", coords)))
+                        .insert(Themed::Tile)
+                        .insert(coords);
+                    cmd.with_children(|parent| match tile {
+                        Tile::Bomb(_) => {
+                            parent
+                                .spawn(SpriteBundle {
+                                    sprite: Sprite {
+                                        custom_size: Some(Vec2::splat(padded)),
+                                        color: board_assets.bomb_material.color,
+                                        ..Default::default()
+                                    },
+                                    transform: Transform::from_xyz(0., 0., 1.),
+                                    texture: board_assets.bomb_material.texture.clone(),
+                                    ..Default::default()
+                                })
+                                .insert(Themed::Bomb);
+                        }
+                        Tile::BombNeighbor(count) => {
+                            parent
+                                .spawn(BoardPlugin::<T>::bomb_count_text_bundle(
+                                    count,
+                                    board_assets,
+                                    number_style,
+                                    padded,
+                                ))
+                                .insert(Themed::Counter)
+                                .with_children(|parent| {
+                                    systems::theme::spawn_count_cue(parent, count, number_style, board_assets, padded);
+                                });
+                        }
+                        Tile::Empty => (),
+                    });
+                    match tile {
+                        Tile::Bomb(_) => cmd.insert(Bomb),
+                        Tile::BombNeighbor(count) => cmd.insert(BombNeighbor { count }),
+                        Tile::Empty => &mut cmd,
+                    };
+                    if !revealed {
+                        cmd.with_children(|parent| {
+                            let cover = systems::uncover::spawn_cover(parent, board_assets, Topology::Square, padded);
+                            covers.push((coords, cover, flagged));
+                        });
+                    }
+                }
+            })
+            .id();
+        for (coords, cover, flagged) in covers {
+            if flagged {
+                systems::mark::set_flags(commands, cover, 1, board_assets, size, None);
+            }
+            board.covers.insert(coords, cover);
+        }
+        board.spawned_chunks.insert(chunk, entity);
+    }
+
+    /// Saves the game and despawns the chunks
+    fn cleanup_board(
+        mut commands: Commands,
+        mut board: ResMut<EndlessBoard>,
+        mut cameras: Query<&mut Transform, With<Camera2d>>,
+    ) {
+        if board.exploded.is_none() {
+            if let Err(e) = board.save() {
+                log::error!("Failed to save the endless game: {}", e);
+            }
+        }
+        for entity in board.spawned_chunks.values() {
+            commands.entity(*entity).despawn_recursive();
+        }
+        commands.remove_resource::<EndlessBoard>();
+        // Bounded boards are centered on the origin
+        for mut transform in cameras.iter_mut() {
+            transform.translation.x = 0.;
+            transform.translation.y = 0.;
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::components::Coordinates;
+use crate::components::{Coordinates, WorldCoordinates};
+use crate::resources::{GameSummary, ModeRun, Player, SoundCue};
+use crate::solver::Hint;
 
 #[derive(Debug, Copy, Clone)]
 pub struct BoardCompletedEvent;
@@ -15,3 +17,72 @@ pub struct TileMarkEvent(pub Coordinates);
 #[derive(Debug, Copy, Clone)]
 pub struct TileTriggerEvent(pub Coordinates);
 
+
+/// Sent for every tile uncovered, by the player or a cascade
+#[derive(Debug, Copy, Clone)]
+pub struct TileUncoveredEvent(pub Coordinates);
+
+
+/// Reverts the last player action, a whole cascade or chord at once
+#[derive(Debug, Copy, Clone)]
+pub struct UndoEvent;
+
+
+/// Applies the last undone player action again
+#[derive(Debug, Copy, Clone)]
+pub struct RedoEvent;
+
+
+/// Asks for the next logical move to be highlighted
+#[derive(Debug, Copy, Clone)]
+pub struct HintRequestEvent;
+
+
+/// Hint shown in response to a `HintRequestEvent`
+#[derive(Debug, Clone)]
+pub struct HintEvent(pub Hint);
+
+
+/// Sent once per board, on the first completion or explosion
+#[derive(Debug, Copy, Clone)]
+pub struct GameOverEvent(pub GameSummary);
+
+
+/// Sound to play for a board event, the playback is left to the app
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SoundCueEvent {
+    pub cue: SoundCue,
+    /// Volume from 0 to 1
+    pub volume: f32,
+}
+
+
+/// Reveals a tile of the endless board
+#[derive(Debug, Copy, Clone)]
+pub struct EndlessTriggerEvent(pub WorldCoordinates);
+
+
+/// Flags or unflags a tile of the endless board
+#[derive(Debug, Copy, Clone)]
+pub struct EndlessMarkEvent(pub WorldCoordinates);
+
+
+/// Sent when a bomb of the endless board explodes, with the final score
+#[derive(Debug, Copy, Clone)]
+pub struct EndlessGameOverEvent {
+    pub cleared: u32,
+}
+
+
+/// Sent when a time attack or mine race ends, with the finished run
+#[derive(Debug, Clone)]
+pub struct ModeOverEvent(pub ModeRun);
+
+
+/// Sent when a versus game is decided, with the final scores
+#[derive(Debug, Copy, Clone)]
+pub struct VersusOverEvent {
+    pub scores: [u16; 2],
+    /// `None` for a draw
+    pub winner: Option<Player>,
+}
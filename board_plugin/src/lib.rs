@@ -1,7 +1,13 @@
+#![allow(clippy::needless_return)]
+
 pub mod components;
 pub mod resources;
+pub mod solver;
+pub mod events;
+pub mod date;
 mod bounds;
-mod events;
+mod endless;
+mod seed;
 mod systems;
 
 use bevy::ecs::schedule::StateData;
@@ -12,16 +18,24 @@ use bevy::utils::HashMap;
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::RegisterInspectable;
 use bounds::Bounds2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use components::*;
-use resources::BoardAssets;
+use resources::{AnimationSettings, BoardAssets, BoardAssetsLoader, NumberStyle, SoundSettings};
 use crate::events::*;
 use crate::resources::tile::Tile;
 use resources::tile_map::TileMap;
 use resources::Board;
 use resources::BoardOptions;
 use resources::BoardPosition;
+use resources::BoardTimer;
+use resources::ProbabilityOverlay;
+use resources::DailyHistory;
+use resources::Stats;
 use resources::TileSize;
 
+pub use endless::EndlessPlugin;
+
 
 pub struct BoardPlugin<T> {
     pub running_state: T,
@@ -33,17 +47,74 @@ impl<T: StateData> Plugin for BoardPlugin<T> {
         app
             .add_system_set(
                 SystemSet::on_enter(self.running_state.clone())
-                    .with_system(Self::create_board),
+                    .with_system(Self::create_board)
+                    .with_system(systems::mode::start_run)
+                    .with_system(systems::versus::start_versus),
             )
             .add_system_set(
                 SystemSet::on_update(self.running_state.clone())
                     .with_system(systems::input::input_handling)
-                    .with_system(systems::uncover::trigger_event_handler),
+                    .with_system(systems::overlay::toggle_probability_overlay)
+                    .with_system(systems::hint::show_hint)
+                    .with_system(systems::hint::clear_hint)
+                    .with_system(systems::summary::tick_timer)
+                    .with_system(
+                        systems::mode::tick_time_attack
+                            .after(systems::summary::tick_timer),
+                    )
+                    .with_system(systems::uncover::trigger_event_handler)
+                    .with_system(systems::versus::play_turns)
+                    .with_system(systems::history::undo)
+                    // Redone explosions and completions end the game in the same frame
+                    .with_system(systems::history::redo.before(systems::summary::summarize_game)),
             )
             .add_system_set(
                 SystemSet::on_in_stack_update(self.running_state.clone())
                     .with_system(systems::uncover::uncover_tiles)
-                    .with_system(systems::mark::mark_tiles),
+                    .with_system(systems::mark::mark_tiles)
+                    .with_system(systems::theme::apply_board_assets)
+                    .with_system(systems::animation::animate)
+                    .with_system(
+                        systems::animation::animate_explosion
+                            .after(systems::uncover::uncover_tiles),
+                    )
+                    .with_system(
+                        systems::sound::emit_sound_cues
+                            .after(systems::uncover::uncover_tiles)
+                            .after(systems::mark::mark_tiles),
+                    )
+                    // Runs after the uncovering so it never decorates a cover being removed
+                    .with_system(
+                        systems::overlay::update_probability_overlay
+                            .after(systems::uncover::uncover_tiles),
+                    )
+                    .with_system(
+                        systems::summary::summarize_game
+                            .after(systems::uncover::uncover_tiles)
+                            // Flagging the last bomb completes a mine race
+                            .after(systems::mark::mark_tiles),
+                    )
+                    .with_system(
+                        systems::summary::record_stats
+                            .after(systems::summary::summarize_game),
+                    )
+                    .with_system(
+                        systems::summary::record_daily
+                            .after(systems::summary::summarize_game),
+                    )
+                    .with_system(
+                        systems::mode::advance_run::<T>
+                            .after(systems::summary::summarize_game),
+                    ),
+            )
+            // Pushing a state over the running one pauses the game
+            .add_system_set(
+                SystemSet::on_pause(self.running_state.clone())
+                    .with_system(systems::pause::hide_board),
+            )
+            .add_system_set(
+                SystemSet::on_resume(self.running_state.clone())
+                    .with_system(systems::pause::show_board),
             )
             .add_system_set(
                 SystemSet::on_exit(self.running_state.clone())
@@ -51,8 +122,25 @@ impl<T: StateData> Plugin for BoardPlugin<T> {
             )
             .add_event::<TileTriggerEvent>()
             .add_event::<TileMarkEvent>()
+            .add_event::<TileUncoveredEvent>()
+            .add_event::<SoundCueEvent>()
             .add_event::<BombExplosionEvent>()
-            .add_event::<BoardCompletedEvent>();
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<UndoEvent>()
+            .add_event::<RedoEvent>()
+            .add_event::<HintRequestEvent>()
+            .add_event::<HintEvent>()
+            .add_event::<GameOverEvent>()
+            .add_event::<ModeOverEvent>()
+            .add_event::<VersusOverEvent>()
+            .add_asset::<BoardAssets>()
+            .init_asset_loader::<BoardAssetsLoader>()
+            .init_resource::<AnimationSettings>()
+            .init_resource::<NumberStyle>()
+            .init_resource::<SoundSettings>()
+            .init_resource::<ProbabilityOverlay>()
+            .init_resource::<Stats>()
+            .init_resource::<DailyHistory>();
         log::info!("Loaded BoardPlugin");
 
         #[cfg(feature = "debug")]
@@ -62,6 +150,10 @@ impl<T: StateData> Plugin for BoardPlugin<T> {
             app.register_inspectable::<BombNeighbor>();
             app.register_inspectable::<Bomb>();
             app.register_inspectable::<Uncover>();
+            app.register_inspectable::<Probability>();
+            app.register_inspectable::<HintHighlight>();
+            app.register_inspectable::<Themed>();
+            app.register_inspectable::<Detonated>();
         }
     }
 }
@@ -72,9 +164,14 @@ impl<T> BoardPlugin<T> {
         mut commands: Commands,
         board_options: Option<Res<BoardOptions>>,
         board_assets: Res<BoardAssets>,
+        number_style: Res<NumberStyle>,
+        // A pre-generated tile map is used as-is instead of placing random bombs
+        preset_tile_map: Option<Res<TileMap>>,
+        overlay: Res<ProbabilityOverlay>,
         // ISSUE: `window` isn't working (likely due to Bevy 0.9)
         // window: Res<WindowDescriptor>,
-        windows: Res<Windows>,
+        // `Windows` is missing when running headless (e.g. with `MinimalPlugins`)
+        windows: Option<Res<Windows>>,
         // mut materials: ResMut<Assets<ColorMaterial>>,
     ) {
         let options = match board_options {
@@ -82,24 +179,34 @@ impl<T> BoardPlugin<T> {
             Some(o) => o.clone(),
         };
 
-        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
-        tile_map.set_bombs(options.bomb_count);
+        let (tile_map, seed) = match preset_tile_map {
+            Some(tile_map) => (tile_map.clone(), None),
+            None => Self::generate_tile_map(&options),
+        };
         #[cfg(feature = "debug")]
         log::info!("{}", tile_map.console_output());
+        let metrics = tile_map.metrics();
+        log::info!("board metrics: {:?}", metrics);
 
+        let window = windows.as_ref().and_then(|w| w.get_primary());
         let tile_size = match options.tile_size {
             TileSize::Fixed(v) => v,
-            TileSize::Adaptive {min, max} => Self::adaptive_tile_size(
-                windows.get_primary().unwrap(),
-                (min, max),
-                (tile_map.width(), tile_map.height()),
-            ),
+            TileSize::Adaptive {min, max} => match window {
+                Some(window) => Self::adaptive_tile_size(
+                    window,
+                    (min, max),
+                    &tile_map,
+                ),
+                None => {
+                    log::warn!("No primary window, using the maximum adaptive tile size");
+                    max
+                }
+            },
         };
 
-        let board_size = Vec2::new(
-            tile_map.width() as f32 * tile_size,
-            tile_map.height() as f32 * tile_size,
-        );
+        let board_size = tile_map
+            .topology()
+            .board_size((tile_map.width(), tile_map.height()), tile_size);
         log::info!("board size: {}", board_size);
 
         let board_position = match options.position {
@@ -133,7 +240,8 @@ impl<T> BoardPlugin<T> {
                         transform: Transform::from_xyz(board_size.x / 2., board_size.y / 2., 0.),
                         ..Default::default()
                     })
-                    .insert(Name::new("Background"));
+                    .insert(Name::new("Background"))
+                    .insert(Themed::Background);
 
                 Self::spawn_tiles(
                     parent,
@@ -141,6 +249,7 @@ impl<T> BoardPlugin<T> {
                     tile_size,
                     options.tile_padding,
                     &board_assets,
+                    &number_style,
                     &mut covered_tiles,
                     &mut safe_start,
                 );
@@ -164,16 +273,48 @@ impl<T> BoardPlugin<T> {
                 tile_size,
                 entity: board_entity,
                 marked_tiles: Vec::new(),
+                hints_used: 0,
+                clicks: 0,
+                // The overlay stays enabled from one board to the next
+                overlay_used: overlay.enabled,
+                metrics,
+                seed,
+                history: Default::default(),
+                game_over: false,
+                lives: options.lives,
+                detonated: Vec::new(),
+                mode: options.mode,
             });
+        commands.insert_resource(BoardTimer::default());
     }
 
 
+    /// Loads the layout file of the options, or places random bombs and returns their seed
+    fn generate_tile_map(options: &BoardOptions) -> (TileMap, Option<u64>) {
+        if let Some(path) = &options.layout {
+            match TileMap::load_layout(path) {
+                Ok(tile_map) => return (tile_map, None),
+                Err(e) => log::error!("Failed to load layout {}: {}", path.display(), e),
+            }
+        }
+        let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1)
+            .with_topology(options.topology)
+            .with_wrap(options.wrap)
+            .with_neighborhood(options.neighborhood)
+            .with_max_mines(if options.multi_mine { Tile::MAX_MINES } else { 1 });
+        tile_map.set_bombs_with(options.bomb_count, &mut StdRng::seed_from_u64(seed));
+        return (tile_map, Some(seed));
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn spawn_tiles(
         parent: &mut ChildBuilder,
         tile_map: &TileMap,
         size: f32,
         padding: f32,
         board_assets: &BoardAssets,
+        number_style: &NumberStyle,
         covered_tiles: &mut HashMap<Coordinates, Entity>,
         safe_start_entity: &mut Option<Entity>,
     ) {
@@ -185,52 +326,69 @@ impl<T> BoardPlugin<T> {
                 };
                 // let mut cmd = parent.spawn_empty();
                 let mut cmd = parent.spawn(SpatialBundle::default());
+                let topology = tile_map.topology();
                 cmd.insert(SpriteBundle {
                         sprite: Sprite {
                             color: board_assets.tile_material.color,
-                            custom_size: Some(Vec2::splat(
-                                size - padding as f32,
-                            )),
+                            custom_size: Some(topology.tile_sprite_size(size - padding)),
                             ..Default::default()
                         },
-                        transform: Transform::from_xyz(
-                            (x as f32 * size) + (size / 2.),
-                            (y as f32 * size) + (size / 2.),
-                            1.,
+                        transform: Transform::from_translation(
+                            topology.tile_center(coordinates, size).extend(1.),
                         ),
-                        texture: board_assets.tile_material.texture.clone(),
+                        texture: board_assets.tile_texture(&board_assets.tile_material, topology),
                         ..Default::default()
                     })
                     .insert(Name::new(format!("Tile ({}, {})", x, y)))
+                    .insert(Themed::Tile)
                     .insert(Coordinates {
                         x: x as u16,
                         y: y as u16,
                     });
 
                 match tile {
-                    Tile::Bomb => {
+                    Tile::Bomb(mines) => {
                         cmd.insert(Bomb);
                         cmd.with_children(|parent| {
-                            parent.spawn(SpriteBundle {
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::splat(size - padding)),
+                            // Multi-mine tiles show a pile of bombs
+                            for index in 0..*mines as usize {
+                                let (position, bomb_size) =
+                                    systems::mark::stacked(index, *mines as usize, size - padding);
+                                parent.spawn(SpriteBundle {
+                                    sprite: Sprite {
+                                        custom_size: Some(Vec2::splat(bomb_size)),
+                                        color: board_assets.bomb_material.color,
+                                        ..Default::default()
+                                    },
+                                    transform: Transform::from_translation(position),
+                                    // `texture` used directly instead of `material` starting in Bevy 0.6
+                                    texture: board_assets.bomb_material.texture.clone(),
                                     ..Default::default()
-                                },
-                                transform: Transform::from_xyz(0., 0., 1.),
-                                // `texture` used directly instead of `material` starting in Bevy 0.6
-                                texture: board_assets.bomb_material.texture.clone(),
-                                ..Default::default()
-                            });
+                                })
+                                .insert(Themed::Bomb);
+                            }
                         });
                     },
                     Tile::BombNeighbor(v) => {
                         cmd.insert(BombNeighbor { count: *v });
                         cmd.with_children(|parent| {
-                            parent.spawn(Self::bomb_count_text_bundle(
-                                *v,
-                                board_assets,
-                                size - padding,
-                            ));
+                            parent
+                                .spawn(Self::bomb_count_text_bundle(
+                                    *v,
+                                    board_assets,
+                                    number_style,
+                                    size - padding,
+                                ))
+                                .insert(Themed::Counter)
+                                .with_children(|parent| {
+                                    systems::theme::spawn_count_cue(
+                                        parent,
+                                        *v,
+                                        number_style,
+                                        board_assets,
+                                        size - padding,
+                                    );
+                                });
                         });
                     },
                     Tile::Empty => (),
@@ -238,19 +396,12 @@ impl<T> BoardPlugin<T> {
 
                 // Add the cover sprites
                 cmd.with_children(|parent| {
-                    let entity = parent
-                        .spawn(SpriteBundle {
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(size - padding)),
-                                color: board_assets.covered_tile_material.color,
-                                ..Default::default()
-                            },
-                            texture: board_assets.covered_tile_material.texture.clone(),
-                            transform: Transform::from_xyz(0., 0., 2.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Tile cover"))
-                        .id();
+                    let entity = systems::uncover::spawn_cover(
+                        parent,
+                        board_assets,
+                        tile_map.topology(),
+                        size - padding,
+                    );
                     covered_tiles.insert(coordinates, entity);
                     if safe_start_entity.is_none() && *tile == Tile::Empty {
                         *safe_start_entity = Some(entity);
@@ -264,9 +415,10 @@ impl<T> BoardPlugin<T> {
     fn bomb_count_text_bundle(
         count: u8,
         board_assets: &BoardAssets,
+        number_style: &NumberStyle,
         size: f32
     ) -> Text2dBundle {
-        let color = board_assets.bomb_counter_color(count);
+        let color = number_style.color(board_assets, count);
 
         return Text2dBundle {
             text: Text {
@@ -283,7 +435,9 @@ impl<T> BoardPlugin<T> {
                     horizontal: HorizontalAlign::Center,
                 },
             },
-            transform: Transform::from_xyz(0., 0., 1.),
+            // Wide neighborhoods count past 9, two digits are shrunk to fit the tile
+            transform: Transform::from_xyz(0., 0., 1.)
+                .with_scale(Vec3::splat(if count >= 10 { 0.6 } else { 1. })),
             ..Default::default()
         };
     }
@@ -291,31 +445,24 @@ impl<T> BoardPlugin<T> {
     fn adaptive_tile_size(
         window: &Window,
         (min, max): (f32, f32),
-        (width, height): (u16, u16),
+        tile_map: &TileMap,
     ) -> f32 {
-        let max_width = window.width() / width as f32;
-        let max_height = window.height() / height as f32;
+        // Size of the board with tiles one unit wide
+        let unit_size = tile_map
+            .topology()
+            .board_size((tile_map.width(), tile_map.height()), 1.);
+        let max_width = window.width() / unit_size.x;
+        let max_height = window.height() / unit_size.y;
         return max_width.min(max_height).clamp(min, max);
     }
 
     fn cleanup_board(board: Res<Board>, mut commands: Commands) {
         commands.entity(board.entity).despawn_recursive();
         commands.remove_resource::<Board>();
+        commands.remove_resource::<BoardTimer>();
     }
 }
 
 
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+mod tests;
@@ -0,0 +1,41 @@
+use std::time::Duration;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// How a cover disappears when its tile is revealed
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RevealStyle {
+    Fade,
+    /// Shrinks horizontally like a card flipping over
+    Flip,
+}
+
+/// Board animation options, purely visual: game state never waits on them
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct AnimationSettings {
+    /// Covers and flags appear and disappear instantly when disabled
+    pub enabled: bool,
+    pub reveal_style: RevealStyle,
+    pub reveal: Duration,
+    /// Delay added for every ring of a cascade, spreading it as a ripple
+    pub ripple_delay: Duration,
+    pub flag_drop: Duration,
+    /// Duration of the board shake and flash on an explosion
+    pub explosion: Duration,
+    /// Board shake amplitude on an explosion, in pixels
+    pub shake_amplitude: f32,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reveal_style: RevealStyle::Fade,
+            reveal: Duration::from_millis(150),
+            ripple_delay: Duration::from_millis(30),
+            flag_drop: Duration::from_millis(200),
+            explosion: Duration::from_millis(500),
+            shake_amplitude: 8.,
+        }
+    }
+}
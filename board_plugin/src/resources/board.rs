@@ -2,6 +2,7 @@ use bevy::log;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use crate::bounds::Bounds2;
+use crate::resources::{BoardMetrics, Change, GameMode, History};
 use crate::{Coordinates, TileMap};
 
 // trait 'Resource' needs to be set explicitly starting in Bevy 0.9
@@ -13,6 +14,25 @@ pub struct Board {
     pub covered_tiles: HashMap<Coordinates, Entity>,
     pub entity: Entity,
     pub marked_tiles: Vec<Coordinates>,
+    /// Number of hints shown on this board
+    pub hints_used: u32,
+    /// Number of uncover and mark attempts
+    pub clicks: u32,
+    /// Was the probability overlay shown on this board
+    pub overlay_used: bool,
+    pub metrics: BoardMetrics,
+    /// Seed of the random bombs, `None` for loaded layouts
+    pub seed: Option<u64>,
+    /// Uncovers and marks of the player, to undo and redo them
+    pub history: History,
+    /// Was the game summarized, later results aren't
+    pub game_over: bool,
+    /// Lives left in lives mode, the first explosion ends the game if `None`
+    pub lives: Option<u8>,
+    /// Bombs uncovered at the cost of a life, shown flagged
+    pub detonated: Vec<Coordinates>,
+    /// Rules deciding when the board is completed
+    pub mode: GameMode,
 }
 
 impl Board {
@@ -29,10 +49,8 @@ impl Board {
 
         // World space to board space
         let coordinates = position - self.bounds.position;
-        Some(Coordinates {
-            x: (coordinates.x / self.tile_size) as u16,
-            y: (coordinates.y / self.tile_size) as u16,
-        })
+        let size = (self.tile_map.width(), self.tile_map.height());
+        self.tile_map.topology().tile_at(coordinates, size, self.tile_size)
     }
 
     /// Retrieves a covered tile entity
@@ -44,25 +62,40 @@ impl Board {
         }
     }
 
-    /// Try to uncover a tile, returning the entity
+    /// Try to uncover a tile, removing its marks, returning the entity
     pub fn try_uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
-        if self.marked_tiles.contains(coords) {
-            self.unmark_tile(coords)?;
+        if !self.covered_tiles.contains_key(coords) {
+            return None;
         }
-        self.covered_tiles.remove(coords)
+        self.unmark_all(coords)?;
+        let entity = self.covered_tiles.remove(coords)?;
+        self.history.record(Change::Uncover(*coords));
+        Some(entity)
     }
 
-    /// Retrieve the adjacent covered tile entities
-    pub fn adjacent_covered_tiles(&self, coord: Coordinates) -> Vec<Entity> {
-        self.tile_map
-            .safe_square_at(coord)
-            .filter_map(|c| self.covered_tiles.get(&c))
-            .copied()
-            .collect()
+    /// Uncovers the tile at `coords` with the board rules and returns the covered tiles
+    /// the cascade goes on to, the neighbors of an empty tile. A bomb costs a life, see
+    /// `try_spend_life`, and explodes the board if there's none left.
+    /// Returns `None` if the tile was already uncovered
+    pub fn reveal(&mut self, coords: Coordinates) -> Option<Vec<Coordinates>> {
+        self.try_uncover_tile(&coords)?;
+        if self.tile_map.is_bomb_at(coords) {
+            self.try_spend_life(coords);
+            return Some(Vec::new());
+        }
+        if self.tile_map.bomb_count_at(coords) > 0 {
+            return Some(Vec::new());
+        }
+        let cascade = self
+            .tile_map
+            .safe_square_at(coords)
+            .filter(|c| self.covered_tiles.contains_key(c))
+            .collect();
+        Some(cascade)
     }
 
-    /// Removes the `coords` from `marked_tiles`
-    fn unmark_tile(&mut self, coords: &Coordinates) -> Option<Coordinates> {
+    /// Removes the `coords` from `marked_tiles`, returning their index
+    fn unmark_tile(&mut self, coords: &Coordinates) -> Option<usize> {
         let pos = match self.marked_tiles.iter().position(|a| a == coords) {
             None => {
                 log::error!("Failed to unmark tile at {}", coords);
@@ -70,24 +103,172 @@ impl Board {
             }
             Some(p) => p,
         };
-        Some(self.marked_tiles.remove(pos))
+        self.marked_tiles.remove(pos);
+        Some(pos)
+    }
+
+    /// Removes every mark of `coords`, recording each removal
+    fn unmark_all(&mut self, coords: &Coordinates) -> Option<()> {
+        while self.marked_tiles.contains(coords) {
+            let index = self.unmark_tile(coords)?;
+            self.history.record(Change::Unmark { coords: *coords, index });
+        }
+        Some(())
+    }
+
+    /// Number of marks on a tile, multi-mine tiles take one per expected bomb.
+    /// Detonated bombs count as marked
+    pub fn marks_at(&self, coords: &Coordinates) -> usize {
+        if self.detonated.contains(coords) {
+            return self.tile_map.mines_at(*coords) as usize;
+        }
+        self.marked_tiles.iter().filter(|c| *c == coords).count()
     }
 
-    /// Is the board complete?
+    /// Retrieves the covered tiles around a bomb neighbor whose marks match its count,
+    /// the tiles a click on the uncovered counter uncovers
+    pub fn chord_tiles(&self, coords: &Coordinates) -> Vec<Entity> {
+        if self.covered_tiles.contains_key(coords) {
+            return Vec::new();
+        }
+        let count = self.tile_map.bomb_count_at(*coords);
+        let marks: usize = self
+            .tile_map
+            .safe_square_at(*coords)
+            .map(|c| self.marks_at(&c))
+            .sum();
+        if count == 0 || marks != count as usize {
+            return Vec::new();
+        }
+        self.tile_map
+            .safe_square_at(*coords)
+            .filter_map(|c| self.tile_to_uncover(&c))
+            .copied()
+            .collect()
+    }
+
+    /// Is a bomb uncovered, other than the ones a life was spent on?
+    pub fn is_exploded(&self) -> bool {
+        self.tile_map.iter().enumerate().any(|(y, line)| {
+            line.iter().enumerate().any(|(x, tile)| {
+                let coords = Coordinates { x: x as u16, y: y as u16 };
+                tile.is_bomb()
+                    && !self.covered_tiles.contains_key(&coords)
+                    && !self.detonated.contains(&coords)
+            })
+        })
+    }
+
+    /// Is the board complete? A mine race needs every bomb flagged, other modes every
+    /// other tile uncovered. Versus games end on their scores instead
     pub fn is_completed(&self) -> bool {
-        self.tile_map.bomb_count() as usize == self.covered_tiles.len()
+        match self.mode {
+            GameMode::MineRace => self.tile_map.iter().enumerate().all(|(y, line)| {
+                line.iter().enumerate().all(|(x, tile)| {
+                    let coords = Coordinates { x: x as u16, y: y as u16 };
+                    self.marks_at(&coords) == tile.mines() as usize
+                })
+            }),
+            GameMode::Classic | GameMode::TimeAttack => {
+                self.tile_map.bomb_tile_count() == self.covered_tiles.len() + self.detonated.len()
+            }
+            GameMode::Versus | GameMode::Flags => false,
+        }
     }
 
-    pub fn try_toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, bool)> {
+    /// Would marking `coords` add a flag where there's no bomb left to flag, the mistake
+    /// a mine race penalizes
+    pub fn is_wrong_mark(&self, coords: &Coordinates) -> bool {
+        let marks = self.marks_at(coords);
+        self.covered_tiles.contains_key(coords)
+            && marks < self.tile_map.max_mines() as usize
+            && marks >= self.tile_map.mines_at(*coords) as usize
+    }
+
+    /// Uncovers the bomb at `coords` claimed in a versus game, flagged like a detonated bomb.
+    /// Returns its cover entity
+    pub fn claim_mine(&mut self, coords: Coordinates) -> Option<Entity> {
+        let entity = self.uncover_tile(coords)?;
+        self.detonated.push(coords);
+        Some(entity)
+    }
+
+    /// Spends a life on the bomb uncovered at `coords`, returns whether the game goes on.
+    /// The last life and boards without lives end it
+    pub fn try_spend_life(&mut self, coords: Coordinates) -> bool {
+        match self.lives {
+            Some(lives) if lives > 1 => {
+                self.lives = Some(lives - 1);
+                self.detonated.push(coords);
+                true
+            }
+            Some(_) => {
+                self.lives = Some(0);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Gives back the life spent on `coords`, when its uncovering is undone.
+    /// Returns whether it was detonated at the cost of a life
+    pub fn restore_life(&mut self, coords: Coordinates) -> bool {
+        let lives = match self.lives {
+            None => return false,
+            Some(l) => l,
+        };
+        let detonated = self.detonated.contains(&coords);
+        self.detonated.retain(|c| *c != coords);
+        // The last life is spent without detonating the bomb
+        if detonated || self.tile_map.is_bomb_at(coords) {
+            self.lives = Some(lives + 1);
+        }
+        detonated
+    }
+
+    /// Adds a mark to a covered tile, or removes all of its marks once it has as many as
+    /// a tile holds bombs. Returns the cover entity and its number of marks
+    pub fn try_toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, usize)> {
         let entity = *self.covered_tiles.get(coords)?;
-        let mark = if self.marked_tiles.contains(coords) {
-            self.unmark_tile(coords)?;
-            false
-        } else {
+        if self.marks_at(coords) < self.tile_map.max_mines() as usize {
             self.marked_tiles.push(*coords);
-            true
-        };
-        Some((entity, mark))
+            self.history.record(Change::Mark(*coords));
+        } else {
+            self.unmark_all(coords)?;
+        }
+        Some((entity, self.marks_at(coords)))
+    }
+
+    /// Covers a tile again with the `entity` cover.
+    /// Undos use it, it isn't recorded in the history
+    pub fn cover_tile(&mut self, coords: Coordinates, entity: Entity) {
+        self.covered_tiles.insert(coords, entity);
+    }
+
+    /// Uncovers a tile without recording it, returning its cover entity
+    pub fn uncover_tile(&mut self, coords: Coordinates) -> Option<Entity> {
+        self.marked_tiles.retain(|c| *c != coords);
+        self.covered_tiles.remove(&coords)
+    }
+
+    /// Marks a covered tile at `index` in `marked_tiles`, or last, without recording it.
+    /// Returns its cover entity
+    pub fn add_mark(&mut self, coords: Coordinates, index: Option<usize>) -> Option<Entity> {
+        let entity = *self.covered_tiles.get(&coords)?;
+        let index = index.unwrap_or(usize::MAX).min(self.marked_tiles.len());
+        self.marked_tiles.insert(index, coords);
+        Some(entity)
+    }
+
+    /// Removes the mark of a covered tile at `index` in `marked_tiles`, or else its last one,
+    /// without recording it. Returns its cover entity
+    pub fn remove_mark(&mut self, coords: Coordinates, index: Option<usize>) -> Option<Entity> {
+        let entity = *self.covered_tiles.get(&coords)?;
+        let index = index
+            .filter(|i| self.marked_tiles.get(*i) == Some(&coords))
+            .or_else(|| self.marked_tiles.iter().rposition(|c| *c == coords))?;
+        self.marked_tiles.remove(index);
+        Some(entity)
     }
 }
 
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
 use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use crate::resources::{Player, SoundCue, Topology};
 
 /// Material of a `Sprite` with a texture and color
 #[derive(Debug, Clone)]
@@ -18,8 +21,9 @@ impl Default for SpriteMaterial {
 }
 
 /// Assets for the board.  Must be used as a resource.
-/// Use the loader for partial setup.
-#[derive(Debug, Clone, Resource)]
+/// Use the loader for partial setup, see `BoardAssetsLoader`.
+#[derive(Debug, Clone, Resource, TypeUuid)]
+#[uuid = "d1c4e526-a86b-49f7-93c4-fdaba5f0f530"]
 pub struct BoardAssets {
     pub label: String,
     pub board_material: SpriteMaterial,
@@ -28,11 +32,25 @@ pub struct BoardAssets {
     pub bomb_counter_font: Handle<Font>,
     pub bomb_counter_colors: Vec<Color>,
     pub flag_material: SpriteMaterial,
+    /// Color of the flags of each player of a versus game, on the flag texture
+    pub player_flag_colors: [Color; 2],
     pub bomb_material: SpriteMaterial,
+    /// Hexagon used as texture by the untextured tiles and covers of hexagonal boards
+    pub hex_texture: Handle<Image>,
+    /// Sound path of every cue, the app loads and plays them
+    pub sounds: HashMap<SoundCue, String>,
 }
 
 impl BoardAssets {
-    /// Default bomb counter color set
+    /// Texture of a tile or cover sprite made of `material` on a `topology` grid
+    pub fn tile_texture(&self, material: &SpriteMaterial, topology: Topology) -> Handle<Image> {
+        match topology {
+            Topology::Hexagonal if material.texture == DEFAULT_IMAGE_HANDLE.typed() => self.hex_texture.clone(),
+            _ => material.texture.clone(),
+        }
+    }
+
+    /// Default bomb counter color set, one color per counter from 1 to 8
     pub fn default_colors() -> Vec<Color> {
         vec![
             Color::WHITE,
@@ -40,9 +58,21 @@ impl BoardAssets {
             Color::YELLOW,
             Color::ORANGE,
             Color::PURPLE,
+            Color::CYAN,
+            Color::PINK,
+            Color::SILVER,
         ]
     }
 
+    /// Default flag colors of the versus players
+    pub fn default_player_colors() -> [Color; 2] {
+        [Color::rgb(0.3, 0.55, 1.), Color::rgb(1., 0.35, 0.3)]
+    }
+
+    pub fn player_flag_color(&self, player: Player) -> Color {
+        self.player_flag_colors[player.index()]
+    }
+
     /// Safely retrieves the color matching a bomb counter
     pub fn bomb_counter_color(&self, counter: u8) -> Color {
         let counter = counter.saturating_sub(1) as usize;
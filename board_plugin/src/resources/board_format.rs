@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::components::Coordinates;
+use crate::resources::layout::LayoutError;
+use crate::resources::tile_map::TileMap;
+
+/// Size of the MBF header: width, height and the mine count
+const MBF_HEADER: usize = 4;
+
+/// File formats a `TileMap` layout can be read from and written to
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BoardFormat {
+    /// ASCII art, see `TileMap::from_ascii`
+    Ascii,
+    /// Minesweeper Board Format: width and height bytes, a big endian `u16` mine count,
+    /// then an `x` and `y` byte per mine, counted from the top left corner
+    Mbf,
+    /// `{"width": 4, "height": 4, "mines": [[0, 0], [3, 3]]}`, counted from the top left corner
+    Json,
+}
+
+/// JSON board schema
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonBoard {
+    width: u16,
+    height: u16,
+    mines: Vec<(u16, u16)>,
+}
+
+impl BoardFormat {
+    /// Picks the format from the file extension, defaulting to ASCII
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("mbf") => Self::Mbf,
+            Some("json") => Self::Json,
+            _ => Self::Ascii,
+        }
+    }
+}
+
+impl TileMap {
+    /// Parses a layout file content in the given format
+    pub fn from_bytes(bytes: &[u8], format: BoardFormat) -> Result<Self, LayoutError> {
+        match format {
+            BoardFormat::Ascii => {
+                let layout = std::str::from_utf8(bytes).map_err(|_| LayoutError::InvalidUtf8)?;
+                Self::from_ascii(layout)
+            }
+            BoardFormat::Mbf => Self::from_mbf(bytes),
+            BoardFormat::Json => {
+                let layout = std::str::from_utf8(bytes).map_err(|_| LayoutError::InvalidUtf8)?;
+                Self::from_json(layout)
+            }
+        }
+    }
+
+    /// Writes the map layout in the given format
+    pub fn to_bytes(&self, format: BoardFormat) -> Result<Vec<u8>, LayoutError> {
+        match format {
+            BoardFormat::Ascii => Ok(self.to_ascii().into_bytes()),
+            BoardFormat::Mbf => self.to_mbf(),
+            BoardFormat::Json => Ok(self.to_json().into_bytes()),
+        }
+    }
+
+    /// Reads a layout file, the format is picked from its extension
+    pub fn load_layout(path: impl AsRef<Path>) -> Result<Self, LayoutError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        Self::from_bytes(&bytes, BoardFormat::from_path(path))
+    }
+
+    /// Writes a layout file, the format is picked from its extension
+    pub fn save_layout(&self, path: impl AsRef<Path>) -> Result<(), LayoutError> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes(BoardFormat::from_path(path))?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Parses a Minesweeper Board Format layout
+    pub fn from_mbf(bytes: &[u8]) -> Result<Self, LayoutError> {
+        if bytes.len() < MBF_HEADER {
+            return Err(LayoutError::Truncated { expected: MBF_HEADER, found: bytes.len() });
+        }
+        let (width, height) = (bytes[0] as u16, bytes[1] as u16);
+        let count = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+        let expected = MBF_HEADER + count * 2;
+        if bytes.len() < expected {
+            return Err(LayoutError::Truncated { expected, found: bytes.len() });
+        }
+        if bytes.len() > expected {
+            return Err(LayoutError::TrailingData(bytes.len() - expected));
+        }
+        let mines: Vec<Coordinates> = bytes[MBF_HEADER..]
+            .chunks_exact(2)
+            .map(|pair| from_top_left(pair[0] as u16, pair[1] as u16, height))
+            .collect::<Result<_, _>>()?;
+        Self::from_mines(width, height, &mines)
+    }
+
+    /// Writes the map as a Minesweeper Board Format layout
+    pub fn to_mbf(&self) -> Result<Vec<u8>, LayoutError> {
+        if self.width() > u8::MAX as u16 || self.height() > u8::MAX as u16 {
+            return Err(LayoutError::TooLarge { width: self.width(), height: self.height() });
+        }
+        let mut bytes = vec![self.width() as u8, self.height() as u8];
+        bytes.extend_from_slice(&self.bomb_count().to_be_bytes());
+        for (x, y) in self.mines_from_top_left() {
+            bytes.push(x as u8);
+            bytes.push(y as u8);
+        }
+        Ok(bytes)
+    }
+
+    /// Parses a JSON layout
+    pub fn from_json(layout: &str) -> Result<Self, LayoutError> {
+        let board: JsonBoard = serde_json::from_str(layout)?;
+        let mines: Vec<Coordinates> = board
+            .mines
+            .iter()
+            .map(|(x, y)| from_top_left(*x, *y, board.height))
+            .collect::<Result<_, _>>()?;
+        Self::from_mines(board.width, board.height, &mines)
+    }
+
+    /// Writes the map as a JSON layout
+    pub fn to_json(&self) -> String {
+        let board = JsonBoard {
+            width: self.width(),
+            height: self.height(),
+            mines: self.mines_from_top_left().collect(),
+        };
+        // A struct of integers always serializes
+        serde_json::to_string(&board).unwrap()
+    }
+
+    /// Mine positions counted from the top left corner, row by row
+    fn mines_from_top_left(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.iter().rev().enumerate().flat_map(|(y, line)| {
+            line.iter()
+                .enumerate()
+                .filter(|(_, tile)| tile.is_bomb())
+                .map(move |(x, _)| (x as u16, y as u16))
+        })
+    }
+}
+
+/// Converts a position counted from the top left corner to `Coordinates`
+fn from_top_left(x: u16, y: u16, height: u16) -> Result<Coordinates, LayoutError> {
+    if y >= height {
+        return Err(LayoutError::MineOutOfBounds(Coordinates { x, y }));
+    }
+    Ok(Coordinates { x, y: height - 1 - y })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = "..1*\n..11\n11..\n*1..\n";
+
+    #[test]
+    fn mbf_round_trips() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        let bytes = tile_map.to_mbf().unwrap();
+        assert_eq!(bytes, vec![4, 4, 0, 2, 3, 0, 0, 3]);
+        assert_eq!(TileMap::from_mbf(&bytes).unwrap().to_ascii(), LAYOUT);
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        let json = tile_map.to_json();
+        assert_eq!(json, r#"{"width":4,"height":4,"mines":[[3,0],[0,3]]}"#);
+        assert_eq!(TileMap::from_json(&json).unwrap().to_ascii(), LAYOUT);
+    }
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(BoardFormat::from_path(Path::new("a.mbf")), BoardFormat::Mbf);
+        assert_eq!(BoardFormat::from_path(Path::new("a.JSON")), BoardFormat::Json);
+        assert_eq!(BoardFormat::from_path(Path::new("a.txt")), BoardFormat::Ascii);
+        assert_eq!(BoardFormat::from_path(Path::new("a")), BoardFormat::Ascii);
+    }
+
+    #[test]
+    fn saves_and_loads_files() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        for name in ["board_plugin_format_test.mbf", "board_plugin_format_test.json"] {
+            let path = std::env::temp_dir().join(name);
+            tile_map.save_layout(&path).unwrap();
+            let loaded = TileMap::load_layout(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.to_ascii(), LAYOUT);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_mbf() {
+        assert!(matches!(
+            TileMap::from_mbf(&[4, 4, 0]),
+            Err(LayoutError::Truncated { expected: 4, found: 3 })
+        ));
+        assert!(matches!(
+            TileMap::from_mbf(&[4, 4, 0, 2, 3, 0]),
+            Err(LayoutError::Truncated { expected: 8, found: 6 })
+        ));
+        assert!(matches!(
+            TileMap::from_mbf(&[4, 4, 0, 1, 3, 0, 0]),
+            Err(LayoutError::TrailingData(1))
+        ));
+        assert!(matches!(
+            TileMap::from_mbf(&[4, 4, 0, 1, 0, 4]),
+            Err(LayoutError::MineOutOfBounds(_))
+        ));
+        assert!(matches!(
+            TileMap::from_mbf(&[4, 4, 0, 2, 1, 1, 1, 1]),
+            Err(LayoutError::DuplicateMine(_))
+        ));
+        assert!(matches!(
+            TileMap::empty(256, 1).to_mbf(),
+            Err(LayoutError::TooLarge { width: 256, height: 1 })
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(matches!(TileMap::from_json("{"), Err(LayoutError::Json(_))));
+        assert!(matches!(
+            TileMap::from_json(r#"{"width":4,"height":4}"#),
+            Err(LayoutError::Json(_))
+        ));
+        assert!(matches!(
+            TileMap::from_json(r#"{"width":4,"height":4,"mines":[[4,0]]}"#),
+            Err(LayoutError::MineOutOfBounds(_))
+        ));
+        assert!(matches!(
+            TileMap::from_bytes(&[0xff], BoardFormat::Json),
+            Err(LayoutError::InvalidUtf8)
+        ));
+        assert!(matches!(
+            TileMap::from_json(r#"{"width":65535,"height":65535,"mines":[]}"#),
+            Err(LayoutError::TooLarge { width: 65535, height: 65535 })
+        ));
+        assert!(matches!(
+            TileMap::from_json(r#"{"width":4,"height":256,"mines":[]}"#),
+            Err(LayoutError::TooLarge { width: 4, height: 256 })
+        ));
+    }
+}
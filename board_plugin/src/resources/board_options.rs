@@ -1,6 +1,12 @@
 use bevy::ecs::system::Resource;
 use bevy::prelude::Vec3;
 use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::date::Date;
+use crate::resources::{GameMode, NeighborhoodRule, Topology};
 
 /// Tile size options
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +42,158 @@ pub struct BoardOptions {
     pub tile_padding: f32,
     /// Does the board generate a safe place to start
     pub safe_start: bool,
+    /// Layout file (ASCII, `.mbf` or `.json`) to load instead of placing random bombs.
+    /// `map_size` and `bomb_count` are then taken from the layout
+    pub layout: Option<PathBuf>,
+    /// Seed of the random bombs, a new one is drawn for every board if `None`
+    pub seed: Option<u64>,
+    /// Grid of random boards, layouts are square
+    #[serde(default)]
+    pub topology: Topology,
+    /// Do the edges of random boards wrap around, left neighboring right and bottom
+    /// neighboring top
+    #[serde(default)]
+    pub wrap: bool,
+    /// Neighbors counted by the numbers of random square boards
+    #[serde(default)]
+    pub neighborhood: NeighborhoodRule,
+    /// Can random tiles hold up to `Tile::MAX_MINES` bombs, `bomb_count` counting each of them
+    #[serde(default)]
+    pub multi_mine: bool,
+    /// Explosions survived in lives mode, each uncovered bomb is flagged and costs a life.
+    /// The first explosion ends the game if `None`
+    #[serde(default)]
+    pub lives: Option<u8>,
+    /// Date of the daily challenge the board is, its result is recorded in `DailyHistory`.
+    /// See `DailyChallenge::options`
+    #[serde(default)]
+    pub daily: Option<Date>,
+    /// Rules of the game, see `GameMode`
+    #[serde(default)]
+    pub mode: GameMode,
+    /// Duration of a time attack
+    #[serde(default = "BoardOptions::default_time_limit")]
+    pub time_limit: Duration,
+    /// Time added for every wrong flag of a mine race
+    #[serde(default = "BoardOptions::default_flag_penalty")]
+    pub flag_penalty: Duration,
+}
+
+/// Reason custom board options are rejected
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BoardOptionsError {
+    Width(u16),
+    Height(u16),
+    BombCount { count: u16, max: u16 },
+}
+
+impl Display for BoardOptionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (min, max) = (BoardOptions::MIN_SIDE, BoardOptions::MAX_SIDE);
+        match self {
+            Self::Width(w) => write!(f, "width {} isn't between {} and {}", w, min, max),
+            Self::Height(h) => write!(f, "height {} isn't between {} and {}", h, min, max),
+            Self::BombCount { count, max } => {
+                write!(f, "{} mines isn't between 1 and {}", count, max)
+            }
+        }
+    }
+}
+
+impl Error for BoardOptionsError {}
+
+/// Standard board presets
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Beginner, Self::Intermediate, Self::Expert];
+
+    pub const fn map_size(&self) -> (u16, u16) {
+        match self {
+            Self::Beginner => (9, 9),
+            Self::Intermediate => (16, 16),
+            Self::Expert => (30, 16),
+        }
+    }
+
+    pub const fn bomb_count(&self) -> u16 {
+        match self {
+            Self::Beginner => 10,
+            Self::Intermediate => 40,
+            Self::Expert => 99,
+        }
+    }
+
+    /// Preset matching a board size and bomb count
+    pub fn from_board(map_size: (u16, u16), bomb_count: u16) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.map_size() == map_size && d.bomb_count() == bomb_count)
+    }
+}
+
+impl BoardOptions {
+    /// Smallest width or height of a custom board
+    pub const MIN_SIDE: u16 = 5;
+    /// Largest width or height of a custom board
+    pub const MAX_SIDE: u16 = 50;
+
+    fn default_time_limit() -> Duration {
+        Duration::from_secs(180)
+    }
+
+    fn default_flag_penalty() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    /// Options of a preset, other fields are left to their defaults
+    pub fn from_difficulty(difficulty: Difficulty) -> Self {
+        Self {
+            map_size: difficulty.map_size(),
+            bomb_count: difficulty.bomb_count(),
+            ..Default::default()
+        }
+    }
+
+    /// Most mines a board can hold, leaving room for a safe start
+    pub fn max_bombs((width, height): (u16, u16)) -> u16 {
+        ((width.saturating_sub(1) as u32) * (height.saturating_sub(1) as u32)).min(u16::MAX as u32) as u16
+    }
+
+    /// Checks the size and bomb count of a custom board
+    pub fn validate(&self) -> Result<(), BoardOptionsError> {
+        let (width, height) = self.map_size;
+        let sides = Self::MIN_SIDE..=Self::MAX_SIDE;
+        if !sides.contains(&width) {
+            return Err(BoardOptionsError::Width(width));
+        }
+        if !sides.contains(&height) {
+            return Err(BoardOptionsError::Height(height));
+        }
+        let max = Self::max_bombs(self.map_size);
+        if !(1..=max).contains(&self.bomb_count) {
+            return Err(BoardOptionsError::BombCount {
+                count: self.bomb_count,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Beginner => write!(f, "Beginner"),
+            Self::Intermediate => write!(f, "Intermediate"),
+            Self::Expert => write!(f, "Expert"),
+        }
+    }
 }
 
 impl Default for TileSize {
@@ -64,6 +222,52 @@ impl Default for BoardOptions {
             tile_size: Default::default(),
             tile_padding: 0.,
             safe_start: false,
+            layout: None,
+            seed: None,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: NeighborhoodRule::Adjacent,
+            multi_mine: false,
+            lives: None,
+            daily: None,
+            mode: GameMode::Classic,
+            time_limit: Self::default_time_limit(),
+            flag_penalty: Self::default_flag_penalty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(map_size: (u16, u16), bomb_count: u16) -> BoardOptions {
+        BoardOptions {
+            map_size,
+            bomb_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for difficulty in Difficulty::ALL {
+            let options = BoardOptions::from_difficulty(difficulty);
+            assert_eq!(options.validate(), Ok(()));
+            assert_eq!(Difficulty::from_board(options.map_size, options.bomb_count), Some(difficulty));
         }
+        assert_eq!(Difficulty::from_board((9, 9), 11), None);
+    }
+
+    #[test]
+    fn rejects_custom_boards_out_of_limits() {
+        assert_eq!(custom((4, 10), 5).validate(), Err(BoardOptionsError::Width(4)));
+        assert_eq!(custom((10, 51), 5).validate(), Err(BoardOptionsError::Height(51)));
+        assert_eq!(
+            custom((10, 10), 82).validate(),
+            Err(BoardOptionsError::BombCount { count: 82, max: 81 })
+        );
+        assert!(custom((10, 10), 0).validate().is_err());
+        assert_eq!(custom((10, 10), 81).validate(), Ok(()));
     }
 }
@@ -0,0 +1,267 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use bevy::prelude::Resource;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::date::Date;
+use crate::seed::splitmix64;
+use crate::resources::storage::{data_path, load_json, save_json};
+use crate::resources::{BoardOptions, GameMode, GameSummary, NeighborhoodRule, Topology};
+
+/// Board of the day, the same for every player as it only depends on the UTC date
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DailyChallenge {
+    pub date: Date,
+}
+
+impl DailyChallenge {
+    /// Today's challenge
+    pub fn today() -> Self {
+        Self { date: Date::today() }
+    }
+
+    /// Seed of the bombs, also drawing the size and mine count
+    pub fn seed(&self) -> u64 {
+        splitmix64((self.date.to_days() as u64).wrapping_add(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// Options of the challenge, with the display settings of `base`. The rules are the
+    /// classic ones whatever the menu settings, and the board starts with an opening
+    pub fn options(&self, base: &BoardOptions) -> BoardOptions {
+        let seed = self.seed();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let width = rng.gen_range(9..=30);
+        let height = rng.gen_range(9..=16);
+        // Between beginner and expert densities
+        let density = rng.gen_range(0.12..=0.2);
+        let bomb_count = ((width * height) as f32 * density).round() as u16;
+        BoardOptions {
+            map_size: (width, height),
+            bomb_count,
+            safe_start: true,
+            layout: None,
+            seed: Some(seed),
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: NeighborhoodRule::Adjacent,
+            multi_mine: false,
+            lives: None,
+            daily: Some(self.date),
+            mode: GameMode::Classic,
+            ..base.clone()
+        }
+    }
+}
+
+/// Result of a daily challenge, the first game of the day
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub date: Date,
+    pub won: bool,
+    /// Time of the game, in seconds
+    pub time: f32,
+    pub three_bv: u32,
+    pub three_bv_per_second: f32,
+    pub clicks: u32,
+}
+
+impl DailyResult {
+    pub fn from_summary(date: Date, summary: &GameSummary) -> Self {
+        Self {
+            date,
+            won: summary.won,
+            time: summary.elapsed.as_secs_f32(),
+            three_bv: summary.metrics.three_bv,
+            three_bv_per_second: summary.three_bv_per_second(),
+            clicks: summary.clicks,
+        }
+    }
+}
+
+/// Summary of a daily result to share with other players
+impl Display for DailyResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Minesweeper daily {}: ", self.date)?;
+        if !self.won {
+            return write!(f, "lost after {:.2}s, {} clicks", self.time, self.clicks);
+        }
+        write!(
+            f,
+            "won in {:.2}s, 3BV {} ({:.2}/s), {} clicks",
+            self.time, self.three_bv, self.three_bv_per_second, self.clicks
+        )
+    }
+}
+
+/// Results of the daily challenges played, persisted to a file
+#[derive(Debug, Clone, Default, Resource, Serialize, Deserialize)]
+pub struct DailyHistory {
+    /// Results sorted by date, one per day
+    pub results: Vec<DailyResult>,
+    /// File the history is saved to, in memory only if `None`
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl DailyHistory {
+    /// File name used in the data directory
+    const FILE_NAME: &'static str = "daily.json";
+
+    /// History file in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_path(Self::FILE_NAME)
+    }
+
+    /// File a result is shared to in the user's data directory
+    pub fn share_path(date: Date) -> Option<PathBuf> {
+        data_path(&format!("daily_{}.txt", date))
+    }
+
+    /// Loads the history saved at `path`, starting empty if there's no file yet
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut history: Self = load_json(&path)?;
+        history.path = Some(path);
+        Ok(history)
+    }
+
+    /// Saves the history atomically, see `save_json`
+    pub fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => save_json(path, self),
+            None => Ok(()),
+        }
+    }
+
+    pub fn result(&self, date: Date) -> Option<&DailyResult> {
+        self.results.iter().find(|r| r.date == date)
+    }
+
+    /// Records a result, returns `false` if the day was already played
+    pub fn record(&mut self, result: DailyResult) -> bool {
+        match self.results.binary_search_by_key(&result.date, |r| r.date) {
+            Ok(_) => false,
+            Err(index) => {
+                self.results.insert(index, result);
+                true
+            }
+        }
+    }
+
+    /// Days won in a row up to `today`, a streak isn't broken before today is played
+    pub fn current_streak(&self, today: Date) -> u32 {
+        let mut day = match self.result(today) {
+            Some(r) if !r.won => return 0,
+            Some(_) => today,
+            None => today.previous(),
+        };
+        let mut streak = 0;
+        while self.result(day).is_some_and(|r| r.won) {
+            streak += 1;
+            day = day.previous();
+        }
+        streak
+    }
+
+    /// Most days won in a row
+    pub fn best_streak(&self) -> u32 {
+        let mut best = 0;
+        let mut streak = 0;
+        let mut last: Option<Date> = None;
+        for result in &self.results {
+            let follows = last.is_some_and(|d| d.to_days() + 1 == result.date.to_days());
+            streak = match (result.won, follows) {
+                (false, _) => 0,
+                (true, true) => streak + 1,
+                (true, false) => 1,
+            };
+            best = best.max(streak);
+            last = Some(result.date);
+        }
+        best
+    }
+
+    /// Shareable text of the result of `date` with the streak it makes
+    pub fn share_text(&self, date: Date) -> Option<String> {
+        let result = self.result(date)?;
+        Some(format!("{}, streak {}", result, self.current_streak(date)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn result(days: i64, won: bool) -> DailyResult {
+        DailyResult {
+            date: Date::from_days(days),
+            won,
+            time: 42.5,
+            three_bv: 30,
+            three_bv_per_second: 30. / 42.5,
+            clicks: 40,
+        }
+    }
+
+    #[test]
+    fn challenge_depends_on_the_date_only() {
+        let base = BoardOptions {
+            topology: Topology::Hexagonal,
+            lives: Some(3),
+            tile_padding: 2.,
+            ..Default::default()
+        };
+        let day = DailyChallenge { date: Date::from_days(20_000) };
+        let options = day.options(&base);
+        assert_eq!(options.validate(), Ok(()));
+        assert_eq!(options.topology, Topology::Square);
+        assert_eq!(options.lives, None);
+        assert_eq!(options.tile_padding, 2.);
+        let again = day.options(&BoardOptions::default());
+        assert_eq!((again.map_size, again.bomb_count, again.seed), (options.map_size, options.bomb_count, options.seed));
+        let next = DailyChallenge { date: Date::from_days(20_001) };
+        assert_ne!(next.seed(), day.seed());
+        for days in 20_000..20_365 {
+            let options = DailyChallenge { date: Date::from_days(days) }.options(&base);
+            assert_eq!(options.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn counts_streaks_of_consecutive_wins() {
+        let mut history = DailyHistory::default();
+        for (days, won) in [(10, true), (11, true), (12, true), (13, false), (15, true), (16, true)] {
+            assert!(history.record(result(days, won)));
+        }
+        assert!(!history.record(result(16, false)));
+        assert_eq!(history.best_streak(), 3);
+        assert_eq!(history.current_streak(Date::from_days(16)), 2);
+        // Today isn't played yet
+        assert_eq!(history.current_streak(Date::from_days(17)), 2);
+        assert_eq!(history.current_streak(Date::from_days(18)), 0);
+        assert_eq!(history.current_streak(Date::from_days(13)), 0);
+
+        let text = history.share_text(Date::from_days(16)).unwrap();
+        assert_eq!(text, "Minesweeper daily 1970-01-17: won in 42.50s, 3BV 30 (0.71/s), 40 clicks, streak 2");
+        assert_eq!(history.share_text(Date::from_days(14)), None);
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let dir = std::env::temp_dir().join("board_plugin_daily_test");
+        let path = dir.join(DailyHistory::FILE_NAME);
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut history = DailyHistory::load(&path).unwrap();
+        history.record(result(3, true));
+        history.record(result(1, false));
+        history.save().unwrap();
+        let loaded = DailyHistory::load(&path).unwrap();
+        assert_eq!(loaded.results, history.results);
+        assert_eq!(loaded.results[0].date, Date::from_days(1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
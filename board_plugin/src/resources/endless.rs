@@ -0,0 +1,534 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use bevy::log;
+use bevy::prelude::{Entity, Resource, Vec2};
+use bevy::utils::{HashMap, HashSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::components::{ChunkCoordinates, WorldCoordinates};
+use crate::resources::storage::{data_path, load_json, save_json};
+use crate::resources::Tile;
+use crate::seed::splitmix64;
+
+/// Endless board options. Must be used as a resource
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+pub struct EndlessOptions {
+    /// Seed of the chunks of a new game, drawn for every game if `None`
+    pub seed: Option<u64>,
+    /// Bombs placed in every chunk, at least `EndlessBoard::MIN_MINES_PER_CHUNK`
+    pub mines_per_chunk: u16,
+    /// Tile world size
+    pub tile_size: f32,
+    /// Padding between tiles
+    pub tile_padding: f32,
+    /// Chunks kept spawned around the ones in view
+    pub margin: i32,
+    /// Size of the view without a window, e.g. when running headless
+    pub view_size: Vec2,
+    /// Folder the game is saved to chunk by chunk and resumed from, not saved if `None`
+    pub save_dir: Option<PathBuf>,
+}
+
+impl EndlessOptions {
+    /// Save folder in the user's data directory
+    pub fn default_save_dir() -> Option<PathBuf> {
+        data_path("endless")
+    }
+}
+
+impl Default for EndlessOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            mines_per_chunk: 40,
+            tile_size: 32.,
+            tile_padding: 1.,
+            margin: 1,
+            view_size: Vec2::new(700., 800.),
+            save_dir: None,
+        }
+    }
+}
+
+/// Tiles of a chunk, by index in the chunk
+#[derive(Debug, Clone)]
+struct Chunk {
+    mines: Vec<bool>,
+    revealed: Vec<bool>,
+    flagged: Vec<bool>,
+    /// Tiles a cascade reached while the chunk wasn't spawned, revealed once it is
+    pending: Vec<bool>,
+}
+
+impl Chunk {
+    /// Were tiles of the chunk revealed, flagged or reached by a cascade
+    fn is_played(&self) -> bool {
+        [&self.revealed, &self.flagged, &self.pending].iter().any(|tiles| tiles.contains(&true))
+    }
+}
+
+/// What the player changed in a chunk, its bombs are generated again from the seed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SavedChunk {
+    /// Indices of the revealed tiles
+    revealed: Vec<u16>,
+    /// Indices of the flagged tiles
+    flagged: Vec<u16>,
+    /// Indices of the tiles waiting for a cascade
+    #[serde(default)]
+    pending: Vec<u16>,
+}
+
+/// Saved game, next to the files of the chunks the player changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SavedGame {
+    seed: u64,
+    /// Bombs of every chunk, the options' are used for saves without it
+    #[serde(default)]
+    mines_per_chunk: Option<u16>,
+    cleared: u32,
+}
+
+/// Unbounded board generated chunk by chunk from a seed as the player explores it
+#[derive(Debug, Resource)]
+pub struct EndlessBoard {
+    pub seed: u64,
+    mines_per_chunk: u16,
+    /// Generated chunks, the ones around the spawned ones and the ones which can't be
+    /// generated again yet, see `evict_chunks`
+    chunks: HashMap<ChunkCoordinates, Chunk>,
+    /// Chunks changed since the last save
+    dirty: HashSet<ChunkCoordinates>,
+    save_dir: Option<PathBuf>,
+    /// Safe tiles revealed, the score
+    pub cleared: u32,
+    /// Bomb which ended the game
+    pub exploded: Option<WorldCoordinates>,
+    pub tile_size: f32,
+    /// Spawned chunk entities
+    pub spawned_chunks: HashMap<ChunkCoordinates, Entity>,
+    /// Cover entities of the spawned covered tiles
+    pub covers: HashMap<WorldCoordinates, Entity>,
+}
+
+impl EndlessBoard {
+    /// Fewest bombs of a chunk, sparser boards open up in cascades across many chunks
+    pub const MIN_MINES_PER_CHUNK: u16 = 20;
+
+    /// Starts a game from `seed`, revealing the safe area around the origin
+    pub fn new(seed: u64, mines_per_chunk: u16, save_dir: Option<PathBuf>) -> Self {
+        let mut board = Self::empty(seed, mines_per_chunk, save_dir);
+        // Nothing is spawned yet, the opening spreads over the 4 chunks the camera starts on
+        let origin_chunks = -1..=0;
+        board.cascade(WorldCoordinates::default(), |_, chunk| {
+            origin_chunks.contains(&chunk.x) && origin_chunks.contains(&chunk.y)
+        });
+        board
+    }
+
+    fn empty(seed: u64, mines_per_chunk: u16, save_dir: Option<PathBuf>) -> Self {
+        Self {
+            seed,
+            mines_per_chunk: mines_per_chunk.clamp(Self::MIN_MINES_PER_CHUNK, ChunkCoordinates::TILES as u16 - 9),
+            chunks: HashMap::default(),
+            dirty: HashSet::default(),
+            save_dir,
+            cleared: 0,
+            exploded: None,
+            tile_size: 1.,
+            spawned_chunks: HashMap::default(),
+            covers: HashMap::default(),
+        }
+    }
+
+    /// Resumes the game saved in `save_dir`, with the density it was started with, or starts
+    /// one from `seed` if there's none
+    pub fn load(save_dir: PathBuf, seed: u64, mines_per_chunk: u16) -> io::Result<Self> {
+        let game: Option<SavedGame> = load_json(&save_dir.join(Self::GAME_FILE))?;
+        Ok(match game {
+            None => Self::new(seed, mines_per_chunk, Some(save_dir)),
+            Some(game) => {
+                // The chunks are read from their files once generated
+                let mines_per_chunk = game.mines_per_chunk.unwrap_or(mines_per_chunk);
+                let mut board = Self::empty(game.seed, mines_per_chunk, Some(save_dir));
+                board.cleared = game.cleared;
+                board
+            }
+        })
+    }
+
+    const GAME_FILE: &'static str = "game.json";
+
+    fn chunk_file(chunk: ChunkCoordinates) -> String {
+        format!("chunk_{}_{}.json", chunk.x, chunk.y)
+    }
+
+    /// Seed of the bombs of a chunk, mixing the game seed and the chunk coordinates
+    fn chunk_seed(&self, chunk: ChunkCoordinates) -> u64 {
+        splitmix64(
+            self.seed
+                ^ (chunk.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (chunk.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F),
+        )
+    }
+
+    /// Generates the chunk, with the player's changes if it was saved
+    fn generate(&self, coords: ChunkCoordinates) -> Chunk {
+        let mut rng = StdRng::seed_from_u64(self.chunk_seed(coords));
+        let mut mines = vec![false; ChunkCoordinates::TILES];
+        let mut remaining = self.mines_per_chunk;
+        while remaining > 0 {
+            let index = rng.gen_range(0..ChunkCoordinates::TILES);
+            // The first reveal at the origin is always an opening
+            let tile = coords.tile(index);
+            if mines[index] || (tile.x.abs() <= 1 && tile.y.abs() <= 1) {
+                continue;
+            }
+            mines[index] = true;
+            remaining -= 1;
+        }
+        let mut chunk = Chunk {
+            mines,
+            revealed: vec![false; ChunkCoordinates::TILES],
+            flagged: vec![false; ChunkCoordinates::TILES],
+            pending: vec![false; ChunkCoordinates::TILES],
+        };
+        if let Some(dir) = &self.save_dir {
+            match load_json::<SavedChunk>(&dir.join(Self::chunk_file(coords))) {
+                Ok(saved) => {
+                    // Indices out of the chunk are skipped rather than trusted
+                    for i in saved.revealed {
+                        if let Some(revealed) = chunk.revealed.get_mut(i as usize) {
+                            *revealed = true;
+                        }
+                    }
+                    for i in saved.flagged {
+                        if let Some(flagged) = chunk.flagged.get_mut(i as usize) {
+                            *flagged = true;
+                        }
+                    }
+                    for i in saved.pending {
+                        if let Some(pending) = chunk.pending.get_mut(i as usize) {
+                            *pending = true;
+                        }
+                    }
+                }
+                Err(e) => log::error!("Failed to load chunk {}: {}", coords, e),
+            }
+        }
+        chunk
+    }
+
+    fn chunk(&mut self, coords: ChunkCoordinates) -> &mut Chunk {
+        if !self.chunks.contains_key(&coords) {
+            let chunk = self.generate(coords);
+            self.chunks.insert(coords, chunk);
+        }
+        self.chunks.get_mut(&coords).expect("the chunk is generated")
+    }
+
+    pub fn is_bomb_at(&mut self, coords: WorldCoordinates) -> bool {
+        self.chunk(coords.chunk()).mines[coords.index_in_chunk()]
+    }
+
+    pub fn is_revealed(&mut self, coords: WorldCoordinates) -> bool {
+        self.chunk(coords.chunk()).revealed[coords.index_in_chunk()]
+    }
+
+    pub fn is_flagged(&mut self, coords: WorldCoordinates) -> bool {
+        self.chunk(coords.chunk()).flagged[coords.index_in_chunk()]
+    }
+
+    pub fn bomb_count_at(&mut self, coords: WorldCoordinates) -> u8 {
+        coords.neighbors().filter(|c| self.is_bomb_at(*c)).count() as u8
+    }
+
+    /// The tile at `coords`, generating the chunks it needs
+    pub fn tile(&mut self, coords: WorldCoordinates) -> Tile {
+        if self.is_bomb_at(coords) {
+            return Tile::Bomb(1);
+        }
+        match self.bomb_count_at(coords) {
+            0 => Tile::Empty,
+            count => Tile::BombNeighbor(count),
+        }
+    }
+
+    /// Reveals a covered tile, spreading over the empty ones like `uncover_tiles`. The
+    /// cascade stops at the chunks which aren't spawned, `continue_cascades` goes on once
+    /// they are. Returns the revealed tiles, a bomb among them ends the game
+    pub fn reveal(&mut self, coords: WorldCoordinates) -> Vec<WorldCoordinates> {
+        if self.exploded.is_some() || self.is_flagged(coords) {
+            return Vec::new();
+        }
+        self.cascade(coords, |board, chunk| board.spawned_chunks.contains_key(&chunk))
+    }
+
+    /// Goes on with the cascades which stopped at chunks spawned since, see `reveal`.
+    /// Returns the revealed tiles
+    pub fn continue_cascades(&mut self) -> Vec<WorldCoordinates> {
+        let mut revealed = Vec::new();
+        if self.exploded.is_some() {
+            return revealed;
+        }
+        let mut pending = Vec::new();
+        for chunk in self.spawned_chunks.keys() {
+            let state = match self.chunks.get_mut(chunk) {
+                Some(s) => s,
+                None => continue,
+            };
+            for (coords, waiting) in chunk.tiles().zip(state.pending.iter_mut()) {
+                if *waiting {
+                    *waiting = false;
+                    pending.push(coords);
+                    self.dirty.insert(*chunk);
+                }
+            }
+        }
+        for coords in pending {
+            revealed.extend(self.cascade(coords, |board, chunk| board.spawned_chunks.contains_key(&chunk)));
+        }
+        revealed
+    }
+
+    /// Reveals `coords` and the tiles around the empty ones, as long as their chunk is
+    /// `in_play`. The tiles out of play are left pending
+    fn cascade(
+        &mut self,
+        coords: WorldCoordinates,
+        in_play: impl Fn(&Self, ChunkCoordinates) -> bool,
+    ) -> Vec<WorldCoordinates> {
+        let mut revealed = Vec::new();
+        let mut queue = VecDeque::from([coords]);
+        while let Some(coords) = queue.pop_front() {
+            if self.is_revealed(coords) {
+                continue;
+            }
+            let tile = self.tile(coords);
+            let chunk = coords.chunk();
+            let state = self.chunk(chunk);
+            state.revealed[coords.index_in_chunk()] = true;
+            state.flagged[coords.index_in_chunk()] = false;
+            self.dirty.insert(chunk);
+            revealed.push(coords);
+            match tile {
+                Tile::Bomb(_) => {
+                    self.exploded = Some(coords);
+                    break;
+                }
+                Tile::BombNeighbor(_) => self.cleared += 1,
+                Tile::Empty => {
+                    self.cleared += 1;
+                    for neighbor in coords.neighbors() {
+                        let chunk = neighbor.chunk();
+                        if in_play(self, chunk) {
+                            queue.push_back(neighbor);
+                        } else if !self.is_revealed(neighbor) {
+                            self.chunk(chunk).pending[neighbor.index_in_chunk()] = true;
+                            self.dirty.insert(chunk);
+                        }
+                    }
+                }
+            }
+        }
+        revealed
+    }
+
+    /// Flags or unflags a covered tile, returns whether it's flagged
+    pub fn toggle_flag(&mut self, coords: WorldCoordinates) -> Option<bool> {
+        if self.exploded.is_some() || self.is_revealed(coords) {
+            return None;
+        }
+        let state = self.chunk(coords.chunk());
+        let flagged = &mut state.flagged[coords.index_in_chunk()];
+        *flagged = !*flagged;
+        let flagged = *flagged;
+        self.dirty.insert(coords.chunk());
+        Some(flagged)
+    }
+
+    /// Writes the chunks changed since the last save
+    pub fn save(&mut self) -> io::Result<()> {
+        let dir = match &self.save_dir {
+            None => return Ok(()),
+            Some(d) => d.clone(),
+        };
+        for coords in self.dirty.iter().copied().collect::<Vec<_>>() {
+            let chunk = &self.chunks[&coords];
+            let indices = |tiles: &[bool]| -> Vec<u16> {
+                tiles.iter().enumerate().filter(|(_, set)| **set).map(|(i, _)| i as u16).collect()
+            };
+            let saved = SavedChunk {
+                revealed: indices(&chunk.revealed),
+                flagged: indices(&chunk.flagged),
+                pending: indices(&chunk.pending),
+            };
+            save_json(&dir.join(Self::chunk_file(coords)), &saved)?;
+            // Chunks which failed to save stay dirty, they can't be evicted
+            self.dirty.remove(&coords);
+        }
+        let game = SavedGame {
+            seed: self.seed,
+            mines_per_chunk: Some(self.mines_per_chunk),
+            cleared: self.cleared,
+        };
+        save_json(&dir.join(Self::GAME_FILE), &game)
+    }
+
+    /// Drops the chunks away from the spawned ones, they're generated again from the seed
+    /// and their saved file when needed. Chunks with changes which aren't saved are kept
+    pub fn evict_chunks(&mut self) {
+        let spawned = &self.spawned_chunks;
+        let near_spawned = |c: &ChunkCoordinates| {
+            (-1..=1).any(|dy| {
+                (-1..=1).any(|dx| spawned.contains_key(&ChunkCoordinates { x: c.x + dx, y: c.y + dy }))
+            })
+        };
+        let saved = self.save_dir.is_some();
+        let dirty = &self.dirty;
+        self.chunks.retain(|coords, chunk| {
+            near_spawned(coords) || dirty.contains(coords) || (!saved && chunk.is_played())
+        });
+    }
+
+    /// Removes the save of a finished game, the next one starts anew
+    pub fn delete_save(&self) -> io::Result<()> {
+        match &self.save_dir {
+            Some(dir) if dir.exists() => fs::remove_dir_all(dir),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32, y: i32) -> WorldCoordinates {
+        WorldCoordinates { x, y }
+    }
+
+    #[test]
+    fn chunks_are_generated_from_the_seed() {
+        let mut a = EndlessBoard::new(7, 40, None);
+        let mut b = EndlessBoard::new(7, 40, None);
+        let chunk = ChunkCoordinates { x: -3, y: 5 };
+        let mines: Vec<bool> = chunk.tiles().map(|c| a.is_bomb_at(c)).collect();
+        assert_eq!(mines.iter().filter(|m| **m).count(), 40);
+        // Generated in any order, e.g. after other chunks
+        b.is_bomb_at(at(100, 100));
+        assert_eq!(chunk.tiles().map(|c| b.is_bomb_at(c)).collect::<Vec<_>>(), mines);
+        let mut other = EndlessBoard::new(8, 40, None);
+        assert_ne!(chunk.tiles().map(|c| other.is_bomb_at(c)).collect::<Vec<_>>(), mines);
+    }
+
+    #[test]
+    fn counts_cross_the_chunk_borders() {
+        // Corners of 4 chunks, with enough bombs for the counts to differ
+        let corners = [at(15, 15), at(-17, 31), at(32, -1)];
+        let mut board = EndlessBoard::new(3, 120, None);
+        let counts: Vec<Tile> = corners.iter().map(|c| board.tile(*c)).collect();
+        // Generating the neighbor chunks first gives the same counts
+        let mut other = EndlessBoard::new(3, 120, None);
+        for coords in corners {
+            for neighbor in coords.neighbors() {
+                other.is_bomb_at(neighbor);
+            }
+        }
+        assert_eq!(corners.iter().map(|c| other.tile(*c)).collect::<Vec<_>>(), counts);
+        assert!(counts.iter().any(|t| matches!(t, Tile::BombNeighbor(_))));
+    }
+
+    #[test]
+    fn starts_with_an_opening_at_the_origin() {
+        let mut board = EndlessBoard::new(11, 40, None);
+        assert_eq!(board.tile(at(0, 0)), Tile::Empty);
+        assert!(board.is_revealed(at(1, 1)));
+        assert!(board.cleared >= 9);
+        assert_eq!(board.exploded, None);
+    }
+
+    #[test]
+    fn sparse_boards_cascade_chunk_by_chunk() {
+        let mut board = EndlessBoard::new(4, 0, None);
+        assert_eq!(board.mines_per_chunk, EndlessBoard::MIN_MINES_PER_CHUNK);
+        let revealed_chunks = |board: &EndlessBoard| -> Vec<ChunkCoordinates> {
+            let mut chunks: Vec<_> = board
+                .chunks
+                .iter()
+                .filter(|(_, c)| c.revealed.contains(&true))
+                .map(|(c, _)| *c)
+                .collect();
+            chunks.sort();
+            chunks
+        };
+        let origin_chunks: Vec<_> = [(-1, -1), (-1, 0), (0, -1), (0, 0)].map(|(x, y)| ChunkCoordinates { x, y }).into();
+        // The opening stops at the chunks around the origin
+        assert_eq!(revealed_chunks(&board), origin_chunks);
+        assert!(board.chunks.values().any(|c| c.pending.contains(&true)));
+
+        // and goes on over the chunks spawned next to them
+        let cleared = board.cleared;
+        for y in -2..=1 {
+            for x in -2..=1 {
+                board.spawned_chunks.insert(ChunkCoordinates { x, y }, Entity::from_raw(0));
+            }
+        }
+        let revealed = board.continue_cascades();
+        assert_eq!(board.cleared as usize, cleared as usize + revealed.len());
+        assert!(revealed_chunks(&board).len() > 4);
+        assert!(revealed_chunks(&board).iter().all(|c| board.spawned_chunks.contains_key(c)));
+        assert!(revealed.iter().all(|c| board.is_revealed(*c)));
+        assert!(board.continue_cascades().is_empty());
+    }
+
+    #[test]
+    fn evicts_chunks_away_from_the_spawned_ones() {
+        let mut board = EndlessBoard::new(2, 40, None);
+        let spawned = ChunkCoordinates { x: 10, y: 10 };
+        board.spawned_chunks.insert(spawned, Entity::from_raw(0));
+        board.is_bomb_at(at(11 * ChunkCoordinates::SIZE, 10 * ChunkCoordinates::SIZE));
+        board.is_bomb_at(at(5 * ChunkCoordinates::SIZE, 5 * ChunkCoordinates::SIZE));
+        board.evict_chunks();
+        assert!(board.chunks.contains_key(&ChunkCoordinates { x: 11, y: 10 }));
+        assert!(!board.chunks.contains_key(&ChunkCoordinates { x: 5, y: 5 }));
+        // Without a save, the chunks of the opening can't be generated again
+        assert!(board.chunks.contains_key(&ChunkCoordinates { x: 0, y: 0 }));
+        assert!(board.is_revealed(at(0, 0)));
+    }
+
+    #[test]
+    fn saves_and_resumes_chunk_by_chunk() {
+        let dir = std::env::temp_dir().join("board_plugin_endless_test");
+        let _ = fs::remove_dir_all(&dir);
+        let mut board = EndlessBoard::load(dir.clone(), 5, 40).unwrap();
+        let far = at(-40, 70);
+        assert_eq!(board.toggle_flag(far), Some(true));
+        board.save().unwrap();
+        assert!(dir.join(EndlessBoard::chunk_file(far.chunk())).exists());
+        // Chunks never changed aren't saved
+        assert!(!dir.join(EndlessBoard::chunk_file(ChunkCoordinates { x: 9, y: 9 })).exists());
+
+        // The saved seed and density are kept whatever the options
+        let mut resumed = EndlessBoard::load(dir.clone(), 6, 60).unwrap();
+        assert_eq!(resumed.seed, 5);
+        assert_eq!(resumed.mines_per_chunk, 40);
+        assert_eq!(resumed.is_bomb_at(far), board.is_bomb_at(far));
+        assert_eq!(resumed.cleared, board.cleared);
+        assert!(resumed.is_flagged(far));
+        assert!(resumed.is_revealed(at(0, 0)));
+
+        // Saved chunks are evicted once far from the spawned ones, and read back
+        resumed.spawned_chunks.insert(ChunkCoordinates { x: 20, y: 20 }, Entity::from_raw(0));
+        resumed.evict_chunks();
+        assert!(resumed.chunks.is_empty());
+        assert!(resumed.is_flagged(far));
+        assert!(resumed.is_revealed(at(0, 0)));
+
+        resumed.delete_save().unwrap();
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Rules of a game, the variants are played on the same boards as the classic game
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Uncover every tile without a bomb
+    #[default]
+    Classic,
+    /// Clear as many boards in a row as possible before `BoardOptions::time_limit`,
+    /// the first explosion ends the run
+    TimeAttack,
+    /// Flag every bomb as fast as possible, each wrong flag is refused and adds
+    /// `BoardOptions::flag_penalty` to the time
+    MineRace,
+    /// Two players take turns and score the bombs they flag. Revealing safe tiles keeps
+    /// the turn, any flag passes it and a revealed bomb scores for the opponent
+    Versus,
+    /// Two players take turns and score the bombs they reveal. Revealing a bomb keeps
+    /// the turn, revealing safe tiles passes it
+    Flags,
+}
+
+impl GameMode {
+    pub const ALL: [Self; 5] = [Self::Classic, Self::TimeAttack, Self::MineRace, Self::Versus, Self::Flags];
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Is `score` a better result than `best`: more boards in a time attack, a shorter
+    /// time in a mine race
+    pub fn is_better(&self, score: f32, best: f32) -> bool {
+        match self {
+            Self::TimeAttack | Self::Versus | Self::Flags => score > best,
+            Self::Classic | Self::MineRace => score < best,
+        }
+    }
+
+    /// Is it a two-player game, see `VersusGame`
+    pub fn is_versus(&self) -> bool {
+        matches!(self, Self::Versus | Self::Flags)
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Classic => write!(f, "classic"),
+            Self::TimeAttack => write!(f, "time attack"),
+            Self::MineRace => write!(f, "mine race"),
+            Self::Versus => write!(f, "versus"),
+            Self::Flags => write!(f, "versus flags"),
+        }
+    }
+}
+
+/// Time attack or mine race being played, kept from one board to the next
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ModeRun {
+    pub mode: GameMode,
+    /// Boards cleared in a time attack
+    pub boards_cleared: u32,
+    /// Time spent on the boards cleared
+    pub previous_boards: Duration,
+    /// Flags refused in a mine race
+    pub wrong_flags: u32,
+    /// Was help used on any board of the run
+    pub assisted: bool,
+    /// Score of the finished run: boards cleared or seconds, `None` while it goes on
+    /// or for a lost mine race
+    pub score: Option<f32>,
+    pub over: bool,
+    /// Set while the next board of a time attack replaces the cleared one
+    pub(crate) continuing: bool,
+}
+
+impl ModeRun {
+    pub fn new(mode: GameMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Time left in a time attack of `limit`, `elapsed` being the time on the current board
+    pub fn time_left(&self, limit: Duration, elapsed: Duration) -> Duration {
+        limit.saturating_sub(self.previous_boards + elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_scores_by_mode() {
+        assert!(GameMode::TimeAttack.is_better(4., 3.));
+        assert!(GameMode::MineRace.is_better(30., 45.));
+        assert!(!GameMode::MineRace.is_better(45., 30.));
+        assert_eq!(GameMode::MineRace.next(), GameMode::Versus);
+        assert_eq!(GameMode::Flags.next(), GameMode::Classic);
+
+        let run = ModeRun {
+            previous_boards: Duration::from_secs(100),
+            ..ModeRun::new(GameMode::TimeAttack)
+        };
+        let limit = Duration::from_secs(180);
+        assert_eq!(run.time_left(limit, Duration::from_secs(30)), Duration::from_secs(50));
+        assert_eq!(run.time_left(limit, Duration::from_secs(90)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use crate::date::Date;
+use crate::resources::storage::{data_path, load_json, save_json};
+use crate::resources::{BoardOptions, Difficulty, GameMode, GameSummary, NeighborhoodRule, Topology};
+
+/// A single entry of the high score table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    /// Time of the win, in seconds
+    pub time: f32,
+    pub date: Date,
+    /// Seed of the board, to replay it
+    pub seed: u64,
+}
+
+/// Fastest wins of every difficulty preset, persisted to a file
+#[derive(Debug, Clone, Default, Resource, Serialize, Deserialize)]
+pub struct HighScores {
+    /// Entries sorted from the fastest, at most `MAX_ENTRIES` by difficulty
+    pub tables: BTreeMap<Difficulty, Vec<HighScore>>,
+    /// File the scores are saved to, in memory only if `None`
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl HighScores {
+    /// Entries kept by difficulty
+    pub const MAX_ENTRIES: usize = 10;
+    /// Longest name accepted
+    pub const MAX_NAME_LENGTH: usize = 12;
+    /// File name used in the data directory
+    const FILE_NAME: &'static str = "high_scores.json";
+
+    /// High scores file in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_path(Self::FILE_NAME)
+    }
+
+    /// Loads the scores saved at `path`, starting empty if there's no file yet
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut scores: Self = load_json(&path)?;
+        scores.path = Some(path);
+        Ok(scores)
+    }
+
+    /// Saves the scores atomically, see `save_json`
+    pub fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => save_json(path, self),
+            None => Ok(()),
+        }
+    }
+
+    /// Difficulty table a game can enter: an unassisted win of a classic game on a random
+    /// square preset board, without the variants nor the free opening of a safe start
+    pub fn eligible(summary: &GameSummary, options: &BoardOptions) -> Option<Difficulty> {
+        if !summary.won || summary.is_assisted() || summary.seed.is_none() {
+            return None;
+        }
+        if options.layout.is_some()
+            || options.safe_start
+            || options.daily.is_some()
+            || options.topology != Topology::Square
+            || options.wrap
+            || options.neighborhood != NeighborhoodRule::Adjacent
+            || options.multi_mine
+            || options.lives.is_some()
+            || options.mode != GameMode::Classic
+        {
+            return None;
+        }
+        Difficulty::from_board(options.map_size, options.bomb_count)
+    }
+
+    /// Rank a time would get in the table of `difficulty`, `None` if it's too slow
+    pub fn rank(&self, difficulty: Difficulty, time: f32) -> Option<usize> {
+        let table = self.tables.get(&difficulty).map(Vec::as_slice).unwrap_or_default();
+        // Ties keep the oldest entry first
+        let rank = table.iter().take_while(|s| s.time <= time).count();
+        (rank < Self::MAX_ENTRIES).then_some(rank)
+    }
+
+    /// Inserts a score, returns its rank or `None` if it didn't make the table
+    pub fn insert(&mut self, difficulty: Difficulty, mut score: HighScore) -> Option<usize> {
+        let rank = self.rank(difficulty, score.time)?;
+        score.name = score.name.trim().chars().take(Self::MAX_NAME_LENGTH).collect();
+        let table = self.tables.entry(difficulty).or_default();
+        table.insert(rank, score);
+        table.truncate(Self::MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+impl Display for HighScores {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.tables.values().all(Vec::is_empty) {
+            return write!(f, "No high scores yet");
+        }
+        for (difficulty, table) in &self.tables {
+            writeln!(f, "{}", difficulty)?;
+            for (rank, score) in table.iter().enumerate() {
+                writeln!(
+                    f,
+                    "  {:>2}. {:<width$} {:>7.2}s  {}  seed {}",
+                    rank + 1,
+                    score.name,
+                    score.time,
+                    score.date,
+                    score.seed,
+                    width = Self::MAX_NAME_LENGTH
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use crate::resources::BoardMetrics;
+
+    fn score(name: &str, time: f32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            time,
+            date: Date::from_days(0),
+            seed: 7,
+        }
+    }
+
+    #[test]
+    fn keeps_the_fastest_ten() {
+        let mut scores = HighScores::default();
+        for i in 0..HighScores::MAX_ENTRIES {
+            assert_eq!(scores.insert(Difficulty::Beginner, score("slow", 20. + i as f32)), Some(i));
+        }
+        assert_eq!(scores.rank(Difficulty::Beginner, 100.), None);
+        assert_eq!(scores.insert(Difficulty::Beginner, score("late", 100.)), None);
+        assert_eq!(scores.insert(Difficulty::Beginner, score("  a very long name ", 5.)), Some(0));
+
+        let table = &scores.tables[&Difficulty::Beginner];
+        assert_eq!(table.len(), HighScores::MAX_ENTRIES);
+        assert_eq!(table[0].name, "a very long ");
+        assert_eq!(table.last().unwrap().time, 28.);
+        // Other presets have their own table
+        assert_eq!(scores.rank(Difficulty::Expert, 100.), Some(0));
+    }
+
+    #[test]
+    fn rejects_assisted_and_custom_games() {
+        let mut summary = GameSummary {
+            won: true,
+            elapsed: Duration::from_secs(10),
+            clicks: 5,
+            hints_used: 0,
+            overlay_used: false,
+            undos: 0,
+            metrics: BoardMetrics::default(),
+            seed: Some(1),
+        };
+        let beginner = BoardOptions::from_difficulty(Difficulty::Beginner);
+        let eligible = |s: &GameSummary| HighScores::eligible(s, &beginner);
+        assert_eq!(eligible(&summary), Some(Difficulty::Beginner));
+        let custom = BoardOptions {
+            bomb_count: 11,
+            ..beginner.clone()
+        };
+        assert_eq!(HighScores::eligible(&summary, &custom), None);
+
+        summary.overlay_used = true;
+        assert_eq!(eligible(&summary), None);
+        summary.overlay_used = false;
+        summary.hints_used = 1;
+        assert_eq!(eligible(&summary), None);
+        summary.hints_used = 0;
+        summary.undos = 1;
+        assert_eq!(eligible(&summary), None);
+        summary.undos = 0;
+        summary.seed = None;
+        assert_eq!(eligible(&summary), None);
+    }
+
+    #[test]
+    fn rejects_variants() {
+        let summary = GameSummary {
+            won: true,
+            elapsed: Duration::from_secs(10),
+            clicks: 5,
+            hints_used: 0,
+            overlay_used: false,
+            undos: 0,
+            metrics: BoardMetrics::default(),
+            seed: Some(1),
+        };
+        let beginner = BoardOptions::from_difficulty(Difficulty::Beginner);
+        let variants = [
+            BoardOptions { safe_start: true, ..beginner.clone() },
+            BoardOptions { daily: Some(Date::today()), ..beginner.clone() },
+            BoardOptions { layout: Some("layout.txt".into()), ..beginner.clone() },
+            BoardOptions { topology: Topology::Hexagonal, ..beginner.clone() },
+            BoardOptions { wrap: true, ..beginner.clone() },
+            BoardOptions { neighborhood: NeighborhoodRule::Knight, ..beginner.clone() },
+            BoardOptions { multi_mine: true, ..beginner.clone() },
+            BoardOptions { lives: Some(3), ..beginner.clone() },
+            BoardOptions { mode: GameMode::MineRace, ..beginner.clone() },
+        ];
+        for options in &variants {
+            assert_eq!(HighScores::eligible(&summary, options), None, "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let dir = std::env::temp_dir().join("board_plugin_high_scores_test");
+        let path = dir.join(HighScores::FILE_NAME);
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut scores = HighScores::load(&path).unwrap();
+        scores.insert(Difficulty::Expert, score("ann", 99.5));
+        scores.save().unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        assert_eq!(loaded.tables, scores.tables);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
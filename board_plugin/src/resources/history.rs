@@ -0,0 +1,107 @@
+use crate::components::Coordinates;
+
+/// Change to the board state, see `History`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Change {
+    /// Tile uncovered, after the removal of its marks
+    Uncover(Coordinates),
+    /// Mark added at the end of `Board::marked_tiles`
+    Mark(Coordinates),
+    /// Mark removed, `index` is the one it had in `Board::marked_tiles`
+    Unmark { coords: Coordinates, index: usize },
+}
+
+/// Undo and redo stacks of a board, one step per player action
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Done steps, the last one gets the changes of the running action
+    done: Vec<Vec<Change>>,
+    undone: Vec<Vec<Change>>,
+    /// Number of steps undone on the board
+    pub undos: u32,
+}
+
+impl History {
+    /// Starts the step of a new player action
+    pub fn begin(&mut self) {
+        if !self.done.last().is_some_and(Vec::is_empty) {
+            self.done.push(Vec::new());
+        }
+    }
+
+    /// Adds a change to the running step, which drops the undone steps.
+    /// Changes made before the first action aren't undoable
+    pub fn record(&mut self, change: Change) {
+        if let Some(step) = self.done.last_mut() {
+            step.push(change);
+            self.undone.clear();
+        }
+    }
+
+    /// Takes the last step with changes, to be reverted in reverse order
+    pub fn undo(&mut self) -> Option<Vec<Change>> {
+        while let Some(step) = self.done.pop() {
+            if !step.is_empty() {
+                self.undos += 1;
+                self.undone.push(step.clone());
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    /// Takes the last undone step, to be applied again in order
+    pub fn redo(&mut self) -> Option<Vec<Change>> {
+        let step = self.undone.pop()?;
+        self.done.push(step.clone());
+        Some(step)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.done.iter().any(|step| !step.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coords(x: u16) -> Coordinates {
+        Coordinates { x, y: 0 }
+    }
+
+    #[test]
+    fn undo_and_redo_whole_steps() {
+        let mut history = History::default();
+        // Before the first action, e.g. the safe start opening
+        history.record(Change::Mark(coords(9)));
+        assert!(!history.can_undo());
+
+        history.begin();
+        history.record(Change::Uncover(coords(0)));
+        history.record(Change::Unmark { coords: coords(1), index: 0 });
+        history.record(Change::Uncover(coords(1)));
+        history.begin();
+        history.record(Change::Mark(coords(2)));
+        // Actions without changes don't take an undo
+        history.begin();
+
+        assert_eq!(history.undo(), Some(vec![Change::Mark(coords(2))]));
+        assert_eq!(history.undo().map(|s| s.len()), Some(3));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.undos, 2);
+
+        assert_eq!(history.redo().map(|s| s.len()), Some(3));
+        assert!(history.can_redo());
+        // Only an action changing the board drops what's left to redo
+        history.begin();
+        assert!(history.can_redo());
+        history.record(Change::Unmark { coords: coords(2), index: 0 });
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(), None);
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use crate::components::Coordinates;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+
+/// Character of a mine in ASCII layouts
+const MINE: char = '*';
+/// Character of an empty tile in ASCII layouts
+const EMPTY: char = '.';
+
+/// Error raised when building a `TileMap` from an explicit layout
+#[derive(Debug)]
+pub enum LayoutError {
+    /// The layout file couldn't be read
+    Io(io::Error),
+    /// The layout has no rows
+    Empty,
+    /// A row doesn't have the same width as the first one
+    RaggedRow { row: usize, expected: usize, found: usize },
+    /// A character isn't part of the layout format
+    InvalidCharacter { row: usize, column: usize, character: char },
+    /// A number doesn't match the mines around it
+    CountMismatch { coordinates: Coordinates, expected: u8, found: u8 },
+    /// A mine lies outside of the map
+    MineOutOfBounds(Coordinates),
+    /// More mines share a tile than it can hold
+    DuplicateMine(Coordinates),
+    /// A text layout isn't valid UTF-8
+    InvalidUtf8,
+    /// A binary layout ends before all of its data
+    Truncated { expected: usize, found: usize },
+    /// A binary layout has bytes left after its data
+    TrailingData(usize),
+    /// A side of the map is longer than `TileMap::MAX_LAYOUT_SIDE`
+    TooLarge { width: u16, height: u16 },
+    /// A JSON layout couldn't be parsed
+    Json(serde_json::Error),
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read layout: {}", e),
+            Self::Empty => write!(f, "layout is empty"),
+            Self::RaggedRow { row, expected, found } => write!(
+                f,
+                "row {} has {} tiles, expected {}",
+                row, found, expected
+            ),
+            Self::InvalidCharacter { row, column, character } => write!(
+                f,
+                "invalid character {:?} at row {}, column {}",
+                character, row, column
+            ),
+            Self::CountMismatch { coordinates, expected, found } => write!(
+                f,
+                "tile {} shows {} but has {} neighbor mines",
+                coordinates, found, expected
+            ),
+            Self::MineOutOfBounds(c) => write!(f, "mine {} is out of bounds", c),
+            Self::DuplicateMine(c) => write!(f, "mine {} is placed too many times", c),
+            Self::InvalidUtf8 => write!(f, "layout is not valid UTF-8"),
+            Self::Truncated { expected, found } => write!(
+                f,
+                "layout has {} bytes, expected {}",
+                found, expected
+            ),
+            Self::TrailingData(n) => write!(f, "layout has {} trailing bytes", n),
+            Self::TooLarge { width, height } => write!(
+                f,
+                "map ({}, {}) is too large, sides are at most {}",
+                width, height, TileMap::MAX_LAYOUT_SIDE
+            ),
+            Self::Json(e) => write!(f, "invalid JSON layout: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl From<io::Error> for LayoutError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LayoutError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl TileMap {
+    /// Parses an ASCII layout, top row first.
+    ///
+    /// `*` is a mine and `.` or a space is a safe tile. Digits are optional bomb
+    /// neighbors, which must match the mines around them.
+    /// The `console_output` format (header, borders and colors) is accepted too.
+    pub fn from_ascii(layout: &str) -> Result<Self, LayoutError> {
+        let rows: Vec<Vec<char>> = layout
+            .lines()
+            .map(strip_ansi)
+            .filter(|line| !is_decoration(line))
+            .map(|line| {
+                let line = line.trim_end_matches('\r');
+                let line = line.strip_prefix('|').unwrap_or(line);
+                let line = line.strip_suffix('|').unwrap_or(line);
+                line.chars().collect()
+            })
+            .collect();
+        let width = match rows.first() {
+            None => return Err(LayoutError::Empty),
+            Some(row) => row.len(),
+        };
+        let height = rows.len();
+        let max = Self::MAX_LAYOUT_SIDE as usize;
+        if width > max || height > max {
+            let side = |n: usize| n.min(u16::MAX as usize) as u16;
+            return Err(LayoutError::TooLarge { width: side(width), height: side(height) });
+        }
+
+        let mut mines = Vec::new();
+        let mut numbers = Vec::new();
+        for (row, line) in rows.iter().enumerate() {
+            if line.len() != width {
+                return Err(LayoutError::RaggedRow { row, expected: width, found: line.len() });
+            }
+            // The first row is the top of the map
+            let y = (height - 1 - row) as u16;
+            for (column, character) in line.iter().copied().enumerate() {
+                let coordinates = Coordinates { x: column as u16, y };
+                match character {
+                    MINE => mines.push(coordinates),
+                    EMPTY | ' ' => (),
+                    '1'..='8' => numbers.push((coordinates, character as u8 - b'0')),
+                    _ => return Err(LayoutError::InvalidCharacter { row, column, character }),
+                }
+            }
+        }
+
+        let tile_map = Self::from_mines(width as u16, height as u16, &mines)?;
+        for (coordinates, found) in numbers {
+            let expected = tile_map.bomb_count_at(coordinates);
+            if found != expected {
+                return Err(LayoutError::CountMismatch { coordinates, expected, found });
+            }
+        }
+        Ok(tile_map)
+    }
+
+    /// Writes the map as an ASCII layout readable by `from_ascii`, multi-mine tiles
+    /// are written as a single mine
+    pub fn to_ascii(&self) -> String {
+        let mut buffer = String::new();
+        for line in self.iter().rev() {
+            for tile in line.iter() {
+                buffer.push(match tile {
+                    // Layouts hold a single bomb per tile
+                    Tile::Bomb(_) => MINE,
+                    Tile::BombNeighbor(v) => (b'0' + v) as char,
+                    Tile::Empty => EMPTY,
+                });
+            }
+            buffer.push('\n');
+        }
+        buffer
+    }
+}
+
+/// Is the line a blank line, a `console_output` header or border?
+fn is_decoration(line: &str) -> bool {
+    let line = line.trim_end_matches('\r');
+    line.is_empty() || line.starts_with("Map (") || line.chars().all(|c| c == '-')
+}
+
+/// Removes terminal color escape sequences
+fn strip_ansi(line: &str) -> String {
+    let mut buffer = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip until the end of the sequence, e.g. `\x1b[1;31m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            buffer.push(c);
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const LAYOUT: &str = "..1*\n..11\n11..\n*1..\n";
+
+    #[test]
+    fn parses_mines_and_numbers() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        assert_eq!((tile_map.width(), tile_map.height()), (4, 4));
+        assert_eq!(tile_map.bomb_count(), 2);
+        assert!(tile_map.is_bomb_at(Coordinates { x: 0, y: 0 }));
+        assert!(tile_map.is_bomb_at(Coordinates { x: 3, y: 3 }));
+        assert_eq!(tile_map[2][0], Tile::Empty);
+        assert_eq!(tile_map[2][3], Tile::BombNeighbor(1));
+    }
+
+    #[test]
+    fn round_trips() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        assert_eq!(tile_map.to_ascii(), LAYOUT);
+        // Numbers are optional
+        let tile_map = TileMap::from_ascii("...*\n....\n....\n*...").unwrap();
+        assert_eq!(tile_map.to_ascii(), LAYOUT);
+    }
+
+    #[test]
+    fn parses_console_output() {
+        let output = "Map (3, 2) with 1 bombs:\n-----\n|\x1b[1;31m*\x1b[0m\x1b[36m1\x1b[0m |\n|\x1b[36m1\x1b[0m\x1b[36m1\x1b[0m |\n-----";
+        let tile_map = TileMap::from_ascii(output).unwrap();
+        assert_eq!(tile_map.to_ascii(), "*1.\n11.\n");
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn round_trips_console_output() {
+        let tile_map = TileMap::from_ascii(LAYOUT).unwrap();
+        let parsed = TileMap::from_ascii(&tile_map.console_output()).unwrap();
+        assert_eq!(parsed.to_ascii(), LAYOUT);
+    }
+
+    #[test]
+    fn loads_layout_file() {
+        let path = std::env::temp_dir().join("board_plugin_layout_test.txt");
+        fs::write(&path, LAYOUT).unwrap();
+        let tile_map = TileMap::load_layout(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(tile_map.to_ascii(), LAYOUT);
+        assert!(matches!(TileMap::load_layout(&path), Err(LayoutError::Io(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        assert!(matches!(TileMap::from_ascii("\n"), Err(LayoutError::Empty)));
+        assert!(matches!(
+            TileMap::from_ascii("..*\n.."),
+            Err(LayoutError::RaggedRow { row: 1, expected: 3, found: 2 })
+        ));
+        assert!(matches!(
+            TileMap::from_ascii("..x"),
+            Err(LayoutError::InvalidCharacter { row: 0, column: 2, character: 'x' })
+        ));
+        assert!(matches!(
+            TileMap::from_ascii("*2"),
+            Err(LayoutError::CountMismatch { expected: 1, found: 2, .. })
+        ));
+        assert!(matches!(
+            TileMap::from_mines(2, 2, &[Coordinates { x: 2, y: 0 }]),
+            Err(LayoutError::MineOutOfBounds(_))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::components::Coordinates;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+
+/// Difficulty metrics of a board layout
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct BoardMetrics {
+    /// Minimum number of clicks needed to clear the board
+    pub three_bv: u32,
+    /// Connected areas of empty tiles, each cleared by a single click
+    pub openings: u32,
+    /// Numbers not bordering an opening, each needing its own click
+    pub isolated_numbers: u32,
+    /// Solver based difficulty, see `solver::difficulty`. The bot plays the whole board
+    /// for it, so it's only computed once the game is over
+    pub difficulty: Option<f32>,
+}
+
+impl TileMap {
+    /// Computes the metrics but the difficulty, which is left to the game summary
+    pub fn metrics(&self) -> BoardMetrics {
+        let openings = self.openings();
+        let isolated_numbers = self.isolated_numbers();
+        BoardMetrics {
+            three_bv: openings + isolated_numbers,
+            openings,
+            isolated_numbers,
+            difficulty: None,
+        }
+    }
+
+    /// Bechtel's Board Benchmark Value: the minimum number of clicks needed to clear the board
+    pub fn three_bv(&self) -> u32 {
+        self.openings() + self.isolated_numbers()
+    }
+
+    /// Number of connected areas of empty tiles
+    pub fn openings(&self) -> u32 {
+        let mut visited = vec![vec![false; self.width() as usize]; self.height() as usize];
+        let mut openings = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if visited[y as usize][x as usize] || self[y as usize][x as usize] != Tile::Empty {
+                    continue;
+                }
+                openings += 1;
+                let mut stack = vec![Coordinates { x, y }];
+                visited[y as usize][x as usize] = true;
+                while let Some(coords) = stack.pop() {
+                    for neighbor in self.safe_square_at(coords) {
+                        if neighbor.x >= self.width() || neighbor.y >= self.height() {
+                            continue;
+                        }
+                        let (nx, ny) = (neighbor.x as usize, neighbor.y as usize);
+                        if !visited[ny][nx] && self[ny][nx] == Tile::Empty {
+                            visited[ny][nx] = true;
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+        openings
+    }
+
+    /// Number of bomb neighbor tiles without any empty neighbor
+    pub fn isolated_numbers(&self) -> u32 {
+        let mut count = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let coords = Coordinates { x, y };
+                if !matches!(self[y as usize][x as usize], Tile::BombNeighbor(_)) {
+                    continue;
+                }
+                let borders_opening = self
+                    .safe_square_at(coords)
+                    .filter(|c| c.x < self.width() && c.y < self.height())
+                    .any(|c| self[c.y as usize][c.x as usize] == Tile::Empty);
+                if !borders_opening {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_opening_clears_corners_map() {
+        let tile_map = TileMap::from_ascii("..1*\n..11\n11..\n*1..").unwrap();
+        assert_eq!(tile_map.openings(), 1);
+        assert_eq!(tile_map.isolated_numbers(), 0);
+        assert_eq!(tile_map.three_bv(), 1);
+    }
+
+    #[test]
+    fn counts_isolated_numbers() {
+        // An opening on the right, only the numbers of column 3 border it
+        let tile_map = TileMap::from_ascii("*2*1.\n1211.").unwrap();
+        assert_eq!(tile_map.openings(), 1);
+        assert_eq!(tile_map.isolated_numbers(), 4);
+        let metrics = tile_map.metrics();
+        assert_eq!(metrics.three_bv, 5);
+        assert_eq!(metrics.openings, 1);
+    }
+}
@@ -1,10 +1,51 @@
 pub(crate) mod tile;
 pub(crate) mod tile_map;
 
+pub use animation_settings::*;
 pub use board::*;
+pub use board_format::BoardFormat;
+pub use game_mode::{GameMode, ModeRun};
+pub use daily::{DailyChallenge, DailyHistory, DailyResult};
+pub use endless::{EndlessBoard, EndlessOptions};
+pub use layout::LayoutError;
+pub use metrics::BoardMetrics;
+pub use neighborhood::NeighborhoodRule;
+pub use tile::Tile;
+pub use tile_map::TileMap;
 pub use board_options::*;
 pub use board_assets::*;
+pub use number_style::*;
+pub use probability_overlay::ProbabilityOverlay;
+pub use high_scores::*;
+pub use history::*;
+pub use sound_settings::*;
+pub use stats::*;
+pub use summary::*;
+pub use theme::*;
+pub use topology::Topology;
+pub use typing::Typing;
+pub use versus::{Player, VersusGame};
 
+mod animation_settings;
 mod board;
 mod board_options;
 mod board_assets;
+mod board_format;
+mod daily;
+mod game_mode;
+mod endless;
+mod high_scores;
+mod history;
+mod layout;
+mod metrics;
+mod neighborhood;
+mod number_style;
+mod probability_overlay;
+mod sound_settings;
+mod stats;
+mod storage;
+mod summary;
+mod theme;
+mod topology;
+mod typing;
+mod versus;
@@ -0,0 +1,116 @@
+use std::fmt::{self, Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// Delta coordinates for all 8 square neighbors
+const ADJACENT_COORDINATES: [(i8, i8); 8] = [
+    (-1, -1), // Bottom left
+    ( 0, -1), // Bottom
+    ( 1, -1), // Bottom right
+    (-1,  0), // Left
+    ( 1,  0), // Right
+    (-1,  1), // Top left
+    ( 0,  1), // Top
+    ( 1,  1), // Top right
+];
+
+/// Delta coordinates for the 4 neighbors sharing a side
+const ORTHOGONAL_COORDINATES: [(i8, i8); 4] = [
+    ( 0, -1), // Bottom
+    (-1,  0), // Left
+    ( 1,  0), // Right
+    ( 0,  1), // Top
+];
+
+/// Delta coordinates for the 8 tiles a chess knight reaches
+const KNIGHT_COORDINATES: [(i8, i8); 8] = [
+    (-1, -2),
+    ( 1, -2),
+    (-2, -1),
+    ( 2, -1),
+    (-2,  1),
+    ( 2,  1),
+    (-1,  2),
+    ( 1,  2),
+];
+
+/// Delta coordinates for the 24 other tiles of the 5x5 square around a tile
+const WIDE_COORDINATES: [(i8, i8); 24] = {
+    let mut deltas = [(0, 0); 24];
+    let mut i = 0;
+    let mut y = -2;
+    while y <= 2 {
+        let mut x = -2;
+        while x <= 2 {
+            if x != 0 || y != 0 {
+                deltas[i] = (x, y);
+                i += 1;
+            }
+            x += 1;
+        }
+        y += 1;
+    }
+    deltas
+};
+
+/// Tiles counted by the numbers of a square grid, which are also the ones chords and
+/// cascades reach. Hexagonal grids always count their 6 adjacent tiles
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum NeighborhoodRule {
+    /// The 8 tiles around, as in classic minesweeper
+    #[default]
+    Adjacent,
+    /// The 4 tiles sharing a side
+    Orthogonal,
+    /// The 8 tiles a chess knight move away
+    Knight,
+    /// The 24 tiles of the 5x5 square around
+    Wide,
+}
+
+impl NeighborhoodRule {
+    pub const ALL: [Self; 4] = [Self::Adjacent, Self::Orthogonal, Self::Knight, Self::Wide];
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|r| r == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Delta coordinates of the neighbors of a square tile
+    pub fn deltas(&self) -> &'static [(i8, i8)] {
+        match self {
+            Self::Adjacent => &ADJACENT_COORDINATES,
+            Self::Orthogonal => &ORTHOGONAL_COORDINATES,
+            Self::Knight => &KNIGHT_COORDINATES,
+            Self::Wide => &WIDE_COORDINATES,
+        }
+    }
+}
+
+impl Display for NeighborhoodRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Adjacent => write!(f, "adjacent"),
+            Self::Orthogonal => write!(f, "orthogonal"),
+            Self::Knight => write!(f, "knight's move"),
+            Self::Wide => write!(f, "5x5"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_are_distinct_and_mutual() {
+        for rule in NeighborhoodRule::ALL {
+            let deltas = rule.deltas();
+            for (i, (x, y)) in deltas.iter().enumerate() {
+                assert_ne!((*x, *y), (0, 0), "{} counts the tile itself", rule);
+                assert!(!deltas[..i].contains(&(*x, *y)), "{} repeats ({}, {})", rule, x, y);
+                assert!(deltas.contains(&(-x, -y)), "{} isn't symmetric", rule);
+            }
+        }
+        assert_eq!(NeighborhoodRule::Wide.deltas().len(), 24);
+    }
+}
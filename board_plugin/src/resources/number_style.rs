@@ -0,0 +1,141 @@
+use std::fmt::{self, Display, Formatter};
+use bevy::prelude::{Color, Resource};
+use serde::{Deserialize, Serialize};
+use crate::resources::BoardAssets;
+
+/// Bomb counter color sets, the colorblind ones avoid the hues each deficiency confuses
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum ColorPalette {
+    /// Colors of the current theme
+    Theme,
+    /// Red-green, weak green perception
+    Deuteranopia,
+    /// Red-green, weak and dark red perception
+    Protanopia,
+    /// Blue-yellow
+    Tritanopia,
+}
+
+impl ColorPalette {
+    pub const ALL: [Self; 4] = [Self::Theme, Self::Deuteranopia, Self::Protanopia, Self::Tritanopia];
+
+    /// Colors of the counters 1 to 8, `None` for the theme ones
+    pub fn colors(&self) -> Option<[Color; 8]> {
+        let hex = match self {
+            Self::Theme => return None,
+            // Blue and orange axis with distinct lightness, no red against green
+            Self::Deuteranopia => [
+                "64B5F6", "FFB000", "FFFFFF", "785EF0", "FE6100", "DC267F", "FFE97F", "9E9E9E",
+            ],
+            // Same axis with reds lightened since they appear dark
+            Self::Protanopia => [
+                "56B4E9", "F0E442", "FFFFFF", "9D8CFF", "E69F00", "CC79A7", "B0E0FF", "9E9E9E",
+            ],
+            // Red and cyan axis, no blue against green or yellow against violet
+            Self::Tritanopia => [
+                "FF6B6B", "33BBEE", "FFFFFF", "EE3377", "00E5B0", "FFB3C1", "B71C1C", "9E9E9E",
+            ],
+        };
+        Some(hex.map(|h| Color::hex(h).unwrap()))
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|p| p == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for ColorPalette {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Theme => write!(f, "theme"),
+            Self::Deuteranopia => write!(f, "deuteranopia"),
+            Self::Protanopia => write!(f, "protanopia"),
+            Self::Tritanopia => write!(f, "tritanopia"),
+        }
+    }
+}
+
+/// Secondary cue telling the bomb counters apart without relying on color
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum NumberCue {
+    None,
+    /// Dark outline around the digit, for contrast against any tile color
+    Outline,
+    /// One pip per neighboring bomb under the digit
+    Pips,
+}
+
+impl NumberCue {
+    pub const ALL: [Self; 3] = [Self::None, Self::Outline, Self::Pips];
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|c| c == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for NumberCue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Outline => write!(f, "outline"),
+            Self::Pips => write!(f, "pips"),
+        }
+    }
+}
+
+/// Accessibility settings of the bomb counters
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Resource, Serialize, Deserialize)]
+pub struct NumberStyle {
+    pub palette: ColorPalette,
+    pub cue: NumberCue,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        Self {
+            palette: ColorPalette::Theme,
+            cue: NumberCue::None,
+        }
+    }
+}
+
+impl NumberStyle {
+    /// Color of a bomb counter, from the palette or else the theme
+    pub fn color(&self, board_assets: &BoardAssets, count: u8) -> Color {
+        match self.palette.colors() {
+            Some(colors) => colors[(count.clamp(1, 8) - 1) as usize],
+            None => board_assets.bomb_counter_color(count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_have_eight_distinct_colors() {
+        for palette in ColorPalette::ALL {
+            let colors = match palette.colors() {
+                Some(c) => c,
+                None => continue,
+            };
+            for (i, a) in colors.iter().enumerate() {
+                assert!(colors[i + 1..].iter().all(|b| a != b), "{} repeats {:?}", palette, a);
+            }
+        }
+        assert_eq!(BoardAssets::default_colors().len(), 8);
+    }
+
+    #[test]
+    fn cycles_through_settings() {
+        let mut palette = ColorPalette::Theme;
+        for _ in 0..ColorPalette::ALL.len() {
+            palette = palette.next();
+        }
+        assert_eq!(palette, ColorPalette::Theme);
+        assert_eq!(NumberCue::Pips.next(), NumberCue::None);
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::{KeyCode, Resource};
+
+/// Overlay showing the mine probability of every covered tile
+#[derive(Debug, Clone, Resource)]
+pub struct ProbabilityOverlay {
+    /// Is the overlay displayed
+    pub enabled: bool,
+    /// Key toggling the overlay
+    pub toggle_key: KeyCode,
+}
+
+impl Default for ProbabilityOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            toggle_key: KeyCode::P,
+        }
+    }
+}
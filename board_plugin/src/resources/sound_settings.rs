@@ -0,0 +1,65 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Board event with a sound, see `SoundCueEvent`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundCue {
+    /// A bomb neighbor uncovered
+    Reveal,
+    /// Empty tiles uncovered, spreading to their neighbors
+    Cascade,
+    Flag,
+    Unflag,
+    Explosion,
+    Win,
+}
+
+impl SoundCue {
+    pub const ALL: [Self; 6] = [
+        Self::Reveal,
+        Self::Cascade,
+        Self::Flag,
+        Self::Unflag,
+        Self::Explosion,
+        Self::Win,
+    ];
+}
+
+impl Display for SoundCue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Reveal => "reveal",
+            Self::Cascade => "cascade",
+            Self::Flag => "flag",
+            Self::Unflag => "unflag",
+            Self::Explosion => "explosion",
+            Self::Win => "win",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Sound cue options
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct SoundSettings {
+    /// No cue is sent while muted
+    pub muted: bool,
+    /// Volume of the cues, from 0 to 1
+    pub volume: f32,
+    /// Shortest delay between two cues of the same kind, so the rings of
+    /// a big cascade don't play a sound every frame
+    pub min_interval: Duration,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            volume: 0.5,
+            min_interval: Duration::from_millis(80),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use crate::resources::storage::{data_path, load_json, save_json};
+use crate::resources::{BoardOptions, GameMode, GameSummary, ModeRun, NeighborhoodRule, TileMap, Topology};
+
+/// How the board of a game was generated
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GenerationMode {
+    Random,
+    /// Random with the first opening uncovered
+    SafeStart,
+    /// Loaded from a layout file
+    Layout(PathBuf),
+    /// Daily challenge, random with the first opening uncovered
+    Daily,
+}
+
+impl GenerationMode {
+    pub fn from_options(options: &BoardOptions) -> Self {
+        match &options.layout {
+            Some(path) => Self::Layout(path.clone()),
+            None if options.daily.is_some() => Self::Daily,
+            None if options.safe_start => Self::SafeStart,
+            None => Self::Random,
+        }
+    }
+}
+
+impl Display for GenerationMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::SafeStart => write!(f, "safe start"),
+            Self::Layout(path) => write!(f, "layout {}", path.display()),
+            Self::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+/// Statistics of a single board configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigStats {
+    pub games: u32,
+    pub wins: u32,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    /// Best time of a win without assistance, in seconds
+    pub best_time: Option<f32>,
+    /// Best 3BV/s of a win without assistance
+    pub best_three_bv_per_second: Option<f32>,
+    /// Games in which a player action was undone
+    #[serde(default)]
+    pub undo_games: u32,
+}
+
+impl ConfigStats {
+    /// Records a finished game, returns whether it set a new best time
+    pub fn record(&mut self, summary: &GameSummary) -> bool {
+        self.games += 1;
+        if summary.undos > 0 {
+            self.undo_games += 1;
+        }
+        if !summary.won {
+            self.current_streak = 0;
+            return false;
+        }
+        self.wins += 1;
+        self.current_streak += 1;
+        self.best_streak = self.best_streak.max(self.current_streak);
+        if summary.is_assisted() {
+            return false;
+        }
+        let speed = summary.three_bv_per_second();
+        if self.best_three_bv_per_second.is_none_or(|best| speed > best) {
+            self.best_three_bv_per_second = Some(speed);
+        }
+        let time = summary.elapsed.as_secs_f32();
+        let record = self.best_time.is_none_or(|best| time < best);
+        if record {
+            self.best_time = Some(time);
+        }
+        record
+    }
+}
+
+/// Statistics of a time attack or mine race on a board configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModeStats {
+    pub mode: GameMode,
+    pub runs: u32,
+    /// Best score of a run without assistance, see `ModeRun::score`
+    pub best: Option<f32>,
+}
+
+impl ModeStats {
+    /// Records a finished run, returns whether it set a new best score
+    pub fn record(&mut self, run: &ModeRun) -> bool {
+        self.mode = run.mode;
+        self.runs += 1;
+        let score = match run.score {
+            Some(s) if !run.assisted => s,
+            _ => return false,
+        };
+        let record = self.best.is_none_or(|best| run.mode.is_better(score, best));
+        if record {
+            self.best = Some(score);
+        }
+        record
+    }
+}
+
+/// Statistics of every board configuration played, persisted to a file
+#[derive(Debug, Clone, Default, Resource, Serialize, Deserialize)]
+pub struct Stats {
+    /// Statistics by configuration, see `Stats::key`
+    pub configs: BTreeMap<String, ConfigStats>,
+    /// Statistics of the mode variants by configuration, see `Stats::mode_key`
+    #[serde(default)]
+    pub modes: BTreeMap<String, ModeStats>,
+    /// Most tiles cleared in an endless game
+    #[serde(default)]
+    pub endless_best: u32,
+    /// File the stats are saved to, in memory only if `None`
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl Stats {
+    /// File name used in the data directory
+    const FILE_NAME: &'static str = "stats.json";
+
+    /// Stats file in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_path(Self::FILE_NAME)
+    }
+
+    /// Loads the stats saved at `path`, starting empty if there's no file yet
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut stats: Self = load_json(&path)?;
+        stats.path = Some(path);
+        Ok(stats)
+    }
+
+    /// Saves the stats atomically, see `save_json`
+    pub fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => save_json(path, self),
+            None => Ok(()),
+        }
+    }
+
+    /// Configuration key of a board, e.g. `16x16 40 bombs, safe start`
+    /// or `16x16 40 bombs, random, hexagonal, wrapped, 3 lives`
+    pub fn key(tile_map: &TileMap, mode: &GenerationMode, lives: Option<u8>) -> String {
+        let mut key = format!(
+            "{}x{} {} bombs, {}",
+            tile_map.width(),
+            tile_map.height(),
+            tile_map.bomb_count(),
+            mode
+        );
+        if tile_map.topology() != Topology::Square {
+            key = format!("{}, {}", key, tile_map.topology());
+        }
+        if tile_map.wraps() {
+            key = format!("{}, wrapped", key);
+        }
+        if tile_map.neighborhood() != NeighborhoodRule::Adjacent {
+            key = format!("{}, {} neighbors", key, tile_map.neighborhood());
+        }
+        if tile_map.max_mines() > 1 {
+            key = format!("{}, multi-mine", key);
+        }
+        if let Some(lives) = lives {
+            key = format!("{}, {} lives", key, lives);
+        }
+        key
+    }
+
+    /// Configuration key of a mode variant, e.g. `time attack 3:00, 9x9 10 bombs, random`
+    pub fn mode_key(mode: GameMode, time_limit: Duration, board_key: &str) -> String {
+        match mode {
+            GameMode::TimeAttack => {
+                let seconds = time_limit.as_secs();
+                format!("{} {}:{:02}, {}", mode, seconds / 60, seconds % 60, board_key)
+            }
+            GameMode::Classic | GameMode::MineRace | GameMode::Versus | GameMode::Flags => {
+                format!("{}, {}", mode, board_key)
+            }
+        }
+    }
+
+    /// Records a finished time attack or mine race, returns whether it set a new best score
+    pub fn record_mode(&mut self, key: String, run: &ModeRun) -> bool {
+        self.modes.entry(key).or_default().record(run)
+    }
+
+    /// Records a finished game, returns whether it set a new best time
+    pub fn record(&mut self, key: String, summary: &GameSummary) -> bool {
+        self.configs.entry(key).or_default().record(summary)
+    }
+
+    /// Marks a game recorded before its first undo, e.g. when the fatal click is undone
+    pub fn record_undo(&mut self, key: String) {
+        self.configs.entry(key).or_default().undo_games += 1;
+    }
+
+    /// Records a finished endless game, returns whether it set a new best
+    pub fn record_endless(&mut self, cleared: u32) -> bool {
+        let record = cleared > self.endless_best;
+        if record {
+            self.endless_best = cleared;
+        }
+        record
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.configs.is_empty() && self.modes.is_empty() && self.endless_best == 0 {
+            return write!(f, "No games played yet");
+        }
+        for (key, stats) in &self.configs {
+            writeln!(f, "{}", key)?;
+            writeln!(
+                f,
+                "  {} games, {} wins, streak {} (best {})",
+                stats.games, stats.wins, stats.current_streak, stats.best_streak
+            )?;
+            if stats.undo_games > 0 {
+                writeln!(f, "  {} games with undo", stats.undo_games)?;
+            }
+            if let (Some(time), Some(speed)) = (stats.best_time, stats.best_three_bv_per_second) {
+                writeln!(f, "  best time {:.2}s, best 3BV/s {:.2}", time, speed)?;
+            }
+        }
+        for (key, stats) in &self.modes {
+            writeln!(f, "{}", key)?;
+            write!(f, "  {} runs", stats.runs)?;
+            match (stats.mode, stats.best) {
+                (_, None) => writeln!(f)?,
+                (GameMode::TimeAttack, Some(best)) => writeln!(f, ", best {} boards", best)?,
+                (_, Some(best)) => writeln!(f, ", best time {:.2}s", best)?,
+            }
+        }
+        if self.endless_best > 0 {
+            writeln!(f, "Endless\n  best {} tiles cleared", self.endless_best)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::resources::storage::temporary_path;
+    use crate::components::Coordinates;
+    use crate::resources::BoardMetrics;
+
+    fn summary(won: bool, seconds: u64) -> GameSummary {
+        GameSummary {
+            won,
+            elapsed: Duration::from_secs(seconds),
+            clicks: 10,
+            hints_used: 0,
+            overlay_used: false,
+            undos: 0,
+            metrics: BoardMetrics {
+                three_bv: 20,
+                ..Default::default()
+            },
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn records_streaks_and_best_times() {
+        let mut stats = ConfigStats::default();
+        assert!(stats.record(&summary(true, 20)));
+        assert!(!stats.record(&summary(true, 30)));
+        assert!(stats.record(&summary(true, 10)));
+        assert!(!stats.record(&summary(false, 5)));
+        assert_eq!((stats.games, stats.wins), (4, 3));
+        assert_eq!((stats.current_streak, stats.best_streak), (0, 3));
+        assert_eq!(stats.best_time, Some(10.));
+        assert_eq!(stats.best_three_bv_per_second, Some(2.));
+
+        let mut assisted = summary(true, 1);
+        assisted.hints_used = 1;
+        assert!(!stats.record(&assisted));
+        assert_eq!(stats.best_time, Some(10.));
+        assert_eq!(stats.current_streak, 1);
+
+        let mut undone = summary(true, 1);
+        undone.undos = 2;
+        assert!(!stats.record(&undone));
+        assert_eq!(stats.best_time, Some(10.));
+        assert_eq!(stats.undo_games, 1);
+    }
+
+    #[test]
+    fn records_mode_bests() {
+        let mut stats = ModeStats::default();
+        let run = |mode, score, assisted| ModeRun {
+            score,
+            assisted,
+            ..ModeRun::new(mode)
+        };
+        assert!(stats.record(&run(GameMode::TimeAttack, Some(3.), false)));
+        assert!(!stats.record(&run(GameMode::TimeAttack, Some(2.), false)));
+        assert!(!stats.record(&run(GameMode::TimeAttack, Some(5.), true)));
+        assert_eq!((stats.runs, stats.best), (3, Some(3.)));
+
+        let mut stats = ModeStats::default();
+        assert!(!stats.record(&run(GameMode::MineRace, None, false)));
+        assert!(stats.record(&run(GameMode::MineRace, Some(40.), false)));
+        assert!(stats.record(&run(GameMode::MineRace, Some(30.), false)));
+        assert_eq!(stats.best, Some(30.));
+
+        let key = Stats::mode_key(GameMode::TimeAttack, Duration::from_secs(150), "9x9 10 bombs, random");
+        assert_eq!(key, "time attack 2:30, 9x9 10 bombs, random");
+    }
+
+    #[test]
+    fn keys_by_configuration() {
+        let tile_map = TileMap::from_ascii("*.\n..").unwrap();
+        let options = BoardOptions {
+            safe_start: true,
+            ..Default::default()
+        };
+        let key = Stats::key(&tile_map, &GenerationMode::from_options(&options), options.lives);
+        assert_eq!(key, "2x2 1 bombs, safe start");
+        let daily = BoardOptions {
+            daily: Some(crate::date::Date::from_days(0)),
+            ..options
+        };
+        let key = Stats::key(&tile_map, &GenerationMode::from_options(&daily), None);
+        assert_eq!(key, "2x2 1 bombs, daily");
+        let tile_map = tile_map.with_topology(Topology::Hexagonal).with_wrap(true);
+        let key = Stats::key(&tile_map, &GenerationMode::Random, None);
+        assert_eq!(key, "2x2 1 bombs, random, hexagonal, wrapped");
+        let tile_map = tile_map.with_topology(Topology::Square).with_neighborhood(NeighborhoodRule::Knight);
+        let key = Stats::key(&tile_map, &GenerationMode::Random, None);
+        assert_eq!(key, "2x2 1 bombs, random, wrapped, knight's move neighbors");
+        let tile_map = TileMap::from_multi_mines(2, 2, &[Coordinates { x: 0, y: 0 }; 2]).unwrap();
+        let key = Stats::key(&tile_map, &GenerationMode::Random, None);
+        assert_eq!(key, "2x2 2 bombs, random, multi-mine");
+        let key = Stats::key(&tile_map, &GenerationMode::Random, Some(3));
+        assert_eq!(key, "2x2 2 bombs, random, multi-mine, 3 lives");
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let dir = std::env::temp_dir().join("board_plugin_stats_test");
+        let path = dir.join(Stats::FILE_NAME);
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut stats = Stats::load(&path).unwrap();
+        assert!(stats.configs.is_empty());
+        stats.record("key".to_string(), &summary(true, 12));
+        stats.save().unwrap();
+        assert!(!temporary_path(&path).exists());
+
+        let loaded = Stats::load(&path).unwrap();
+        assert_eq!(loaded.configs, stats.configs);
+        assert!(stats.record_endless(40));
+        assert!(!stats.record_endless(30));
+        assert_eq!(stats.endless_best, 40);
+        fs::write(&path, "{").unwrap();
+        assert!(Stats::load(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// File named `file_name` in the game's folder of the user's data directory
+pub(crate) fn data_path(file_name: &str) -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("minesweeper").join(file_name))
+}
+
+/// Reads a JSON file, returning the default value if there's no file yet
+pub(crate) fn load_json<T: DeserializeOwned + Default>(path: &Path) -> io::Result<T> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a JSON file to a temporary file then moves it over `path`,
+/// so a crash never leaves a truncated file behind
+pub(crate) fn save_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = temporary_path(path);
+    fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(&tmp, path)
+}
+
+pub(crate) fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
@@ -0,0 +1,68 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+use bevy::prelude::Resource;
+use crate::resources::BoardMetrics;
+
+/// Time spent on the current board, frozen once the game is over
+#[derive(Debug, Copy, Clone, Default, Resource)]
+pub struct BoardTimer {
+    pub elapsed: Duration,
+    pub stopped: bool,
+}
+
+/// End of game summary
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameSummary {
+    pub won: bool,
+    pub elapsed: Duration,
+    /// Tiles the player tried to uncover or mark
+    pub clicks: u32,
+    pub hints_used: u32,
+    /// Was the probability overlay shown during the game
+    pub overlay_used: bool,
+    /// Player actions undone during the game
+    pub undos: u32,
+    pub metrics: BoardMetrics,
+    /// Seed of the random bombs, `None` for loaded layouts
+    pub seed: Option<u64>,
+}
+
+impl GameSummary {
+    /// Did the player get help during the game?
+    pub fn is_assisted(&self) -> bool {
+        self.hints_used > 0 || self.overlay_used || self.undos > 0
+    }
+
+    /// 3BV cleared per second, only meaningful for won games
+    pub fn three_bv_per_second(&self) -> f32 {
+        let seconds = self.elapsed.as_secs_f32();
+        if seconds <= 0. {
+            return 0.;
+        }
+        self.metrics.three_bv as f32 / seconds
+    }
+
+    /// Share of the clicks that were necessary, only meaningful for won games
+    pub fn efficiency(&self) -> f32 {
+        if self.clicks == 0 {
+            return 0.;
+        }
+        self.metrics.three_bv as f32 / self.clicks as f32
+    }
+}
+
+impl Display for GameSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {:.2}s, 3BV {} ({:.2}/s), {} clicks ({:.0}% efficiency), difficulty {:.1}",
+            if self.won { "Won" } else { "Lost" },
+            self.elapsed.as_secs_f32(),
+            self.metrics.three_bv,
+            self.three_bv_per_second(),
+            self.clicks,
+            self.efficiency() * 100.,
+            self.metrics.difficulty.unwrap_or_default()
+        )
+    }
+}
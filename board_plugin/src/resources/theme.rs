@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use bevy::asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use serde::{Deserialize, Serialize};
+use crate::resources::{BoardAssets, SoundCue, SpriteMaterial};
+
+/// Material of a theme file, missing fields use the classic theme
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaterialFile {
+    /// Hex color, e.g. `#FF8800` or `#FF880080`
+    pub color: Option<String>,
+    /// Texture path, relative to the assets folder
+    pub texture: Option<String>,
+}
+
+/// Content of a `.theme.json` file, missing fields use the classic theme
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeFile {
+    pub label: Option<String>,
+    pub board: MaterialFile,
+    pub tile: MaterialFile,
+    pub covered_tile: MaterialFile,
+    pub flag: MaterialFile,
+    pub bomb: MaterialFile,
+    /// Font path, relative to the assets folder
+    pub font: Option<String>,
+    /// Hex colors of the bomb counters, from 1
+    pub bomb_counter_colors: Option<Vec<String>>,
+    /// Hex colors of the flags of the two versus players
+    pub player_flag_colors: Option<[String; 2]>,
+    /// Hexagon texture path of the tiles of hexagonal boards
+    pub hex_texture: Option<String>,
+    /// Sound paths by cue, relative to the assets folder
+    pub sounds: HashMap<SoundCue, String>,
+}
+
+/// Invalid theme file
+#[derive(Debug)]
+pub enum ThemeError {
+    InvalidColor(String),
+    Json(serde_json::Error),
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidColor(c) => write!(f, "invalid color {:?}, expected #RRGGBB or #RRGGBBAA", c),
+            Self::Json(e) => write!(f, "invalid theme: {}", e),
+        }
+    }
+}
+
+impl Error for ThemeError {}
+
+impl From<serde_json::Error> for ThemeError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Classic theme values used for missing fields
+struct MaterialDefaults {
+    color: Color,
+    texture: Option<&'static str>,
+}
+
+const BOARD: MaterialDefaults = MaterialDefaults { color: Color::WHITE, texture: None };
+const TILE: MaterialDefaults = MaterialDefaults { color: Color::DARK_GRAY, texture: None };
+const COVERED_TILE: MaterialDefaults = MaterialDefaults { color: Color::GRAY, texture: None };
+const FLAG: MaterialDefaults = MaterialDefaults { color: Color::WHITE, texture: Some("sprites/flag.png") };
+const BOMB: MaterialDefaults = MaterialDefaults { color: Color::WHITE, texture: Some("sprites/bomb.png") };
+const FONT: &str = "fonts/pixeled.ttf";
+const HEX_TEXTURE: &str = "sprites/hex.png";
+const SOUNDS: &str = "sounds";
+
+fn parse_color(hex: &str) -> Result<Color, ThemeError> {
+    Color::hex(hex.trim_start_matches('#')).map_err(|_| ThemeError::InvalidColor(hex.to_string()))
+}
+
+impl ThemeFile {
+    pub fn from_json(bytes: &[u8]) -> Result<Self, ThemeError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Asset paths the theme needs, defaults included
+    pub fn dependencies(&self) -> Vec<String> {
+        let materials = [
+            (&self.board, BOARD),
+            (&self.tile, TILE),
+            (&self.covered_tile, COVERED_TILE),
+            (&self.flag, FLAG),
+            (&self.bomb, BOMB),
+        ];
+        let mut paths: Vec<String> = materials
+            .into_iter()
+            .filter_map(|(m, d)| m.texture.clone().or_else(|| d.texture.map(String::from)))
+            .collect();
+        paths.push(self.font.clone().unwrap_or_else(|| FONT.to_string()));
+        paths.push(self.hex_texture.clone().unwrap_or_else(|| HEX_TEXTURE.to_string()));
+        paths
+    }
+
+    /// Builds the board assets, `load_image` and `load_font` turn asset paths into handles
+    pub fn to_board_assets(
+        &self,
+        load_image: impl Fn(&str) -> Handle<Image>,
+        load_font: impl Fn(&str) -> Handle<Font>,
+    ) -> Result<BoardAssets, ThemeError> {
+        let material = |file: &MaterialFile, defaults: MaterialDefaults| -> Result<SpriteMaterial, ThemeError> {
+            let color = match &file.color {
+                Some(hex) => parse_color(hex)?,
+                None => defaults.color,
+            };
+            let texture = match file.texture.as_deref().or(defaults.texture) {
+                Some(path) => load_image(path),
+                None => DEFAULT_IMAGE_HANDLE.typed(),
+            };
+            Ok(SpriteMaterial { color, texture })
+        };
+        let bomb_counter_colors = match &self.bomb_counter_colors {
+            Some(colors) => colors.iter().map(|c| parse_color(c)).collect::<Result<_, _>>()?,
+            None => BoardAssets::default_colors(),
+        };
+        let player_flag_colors = match &self.player_flag_colors {
+            Some([one, two]) => [parse_color(one)?, parse_color(two)?],
+            None => BoardAssets::default_player_colors(),
+        };
+        let sounds = SoundCue::ALL
+            .into_iter()
+            .map(|cue| {
+                let path = self.sounds.get(&cue).cloned();
+                (cue, path.unwrap_or_else(|| format!("{}/{}.wav", SOUNDS, cue)))
+            })
+            .collect();
+        Ok(BoardAssets {
+            label: self.label.clone().unwrap_or_else(|| "Classic".to_string()),
+            board_material: material(&self.board, BOARD)?,
+            tile_material: material(&self.tile, TILE)?,
+            covered_tile_material: material(&self.covered_tile, COVERED_TILE)?,
+            bomb_counter_font: load_font(self.font.as_deref().unwrap_or(FONT)),
+            bomb_counter_colors,
+            flag_material: material(&self.flag, FLAG)?,
+            player_flag_colors,
+            bomb_material: material(&self.bomb, BOMB)?,
+            hex_texture: load_image(self.hex_texture.as_deref().unwrap_or(HEX_TEXTURE)),
+            sounds,
+        })
+    }
+}
+
+impl BoardAssets {
+    /// Classic theme, loading its sprites and font with `asset_server`
+    pub fn classic(asset_server: &AssetServer) -> Self {
+        ThemeFile::default()
+            .to_board_assets(|p| asset_server.load(p), |p| asset_server.load(p))
+            .expect("the classic theme is valid")
+    }
+}
+
+/// Loads `.theme.json` files as `BoardAssets`
+#[derive(Debug, Default)]
+pub struct BoardAssetsLoader;
+
+impl AssetLoader for BoardAssetsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme = ThemeFile::from_json(bytes)?;
+            let board_assets = theme.to_board_assets(
+                |p| load_context.get_handle(AssetPath::new(p.into(), None)),
+                |p| load_context.get_handle(AssetPath::new(p.into(), None)),
+            )?;
+            let dependencies = theme
+                .dependencies()
+                .into_iter()
+                .map(|p| AssetPath::new(p.into(), None))
+                .collect();
+            load_context.set_default_asset(LoadedAsset::new(board_assets).with_dependencies(dependencies));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.json"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(theme: &ThemeFile) -> Result<BoardAssets, ThemeError> {
+        theme.to_board_assets(|_| Handle::default(), |_| Handle::default())
+    }
+
+    #[test]
+    fn partial_theme_falls_back_to_classic() {
+        let theme = ThemeFile::from_json(br##"{"label": "Dark", "board": {"color": "#101010"}}"##).unwrap();
+        let assets = build(&theme).unwrap();
+        assert_eq!(assets.label, "Dark");
+        assert_eq!(assets.board_material.color, Color::hex("101010").unwrap());
+        assert_eq!(assets.tile_material.color, Color::DARK_GRAY);
+        assert_eq!(assets.bomb_counter_colors, BoardAssets::default_colors());
+        assert_eq!(assets.player_flag_colors, BoardAssets::default_player_colors());
+        assert!(theme.dependencies().contains(&"sprites/flag.png".to_string()));
+        assert!(theme.dependencies().contains(&FONT.to_string()));
+    }
+
+    #[test]
+    fn rejects_invalid_themes() {
+        let theme = ThemeFile::from_json(br#"{"bomb_counter_colors": ["red"]}"#).unwrap();
+        assert!(matches!(build(&theme), Err(ThemeError::InvalidColor(_))));
+        let theme = ThemeFile::from_json(br##"{"player_flag_colors": ["#0000FF", "blue"]}"##).unwrap();
+        assert!(matches!(build(&theme), Err(ThemeError::InvalidColor(_))));
+        assert!(matches!(ThemeFile::from_json(br#"{"colour": 1}"#), Err(ThemeError::Json(_))));
+    }
+
+    #[test]
+    fn theme_sounds_override_defaults() {
+        let theme = ThemeFile::from_json(br#"{"sounds": {"win": "sounds/fanfare.ogg"}}"#).unwrap();
+        let assets = build(&theme).unwrap();
+        assert_eq!(assets.sounds.len(), SoundCue::ALL.len());
+        assert_eq!(assets.sounds[&SoundCue::Win], "sounds/fanfare.ogg");
+        assert_eq!(assets.sounds[&SoundCue::Cascade], "sounds/cascade.wav");
+        assert!(ThemeFile::from_json(br#"{"sounds": {"boom": "a.wav"}}"#).is_err());
+    }
+
+    #[test]
+    fn shipped_themes_are_valid() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/themes");
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+            build(&ThemeFile::from_json(&bytes).unwrap()).unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+}
@@ -4,8 +4,8 @@ use colored::Colorize;
 /// Enum describing a Minesweeper tile
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Tile {
-    /// Is a bomb
-    Bomb,
+    /// Holds bombs, one unless the map allows multi-mine tiles
+    Bomb(u8),
     /// is a bomb neighbor
     BombNeighbor(u8),
     /// empty tile
@@ -13,9 +13,20 @@ pub enum Tile {
 }
 
 impl Tile {
+    /// Most bombs a multi-mine tile holds
+    pub const MAX_MINES: u8 = 3;
+
     /// Is the tile a bomb?
     pub const fn is_bomb(&self) -> bool {
-        return matches!(self, Self::Bomb);
+        return matches!(self, Self::Bomb(_));
+    }
+
+    /// Number of bombs in the tile
+    pub const fn mines(&self) -> u8 {
+        match self {
+            Self::Bomb(n) => *n,
+            _ => 0,
+        }
     }
 
     #[cfg(feature = "debug")]
@@ -23,7 +34,8 @@ impl Tile {
         format!(
             "{}",
             match self {
-                Tile::Bomb => "*".bright_red(),
+                Tile::Bomb(1) => "*".bright_red(),
+                Tile::Bomb(n) => n.to_string().on_red(),
                 Tile::BombNeighbor(v) => match v {
                     1 => "1".cyan(),
                     2 => "2".green(),
@@ -1,51 +1,113 @@
 use crate::components::Coordinates;
+use crate::resources::layout::LayoutError;
 use crate::resources::tile::Tile;
+use crate::resources::{NeighborhoodRule, Topology};
+use bevy::prelude::Resource;
 use rand::{thread_rng, Rng};
 use std::ops::{Deref, DerefMut};
 
-/// Delta coordinates for all 8 square neighbors
-const SQUARE_COORDINATES: [(i8, i8); 8] = [
-    (-1, -1), // Bottom left
-    ( 0, -1), // Bottom
-    ( 1, -1), // Bottom right
-    (-1,  0), // Left
-    ( 1,  0), // Right
-    (-1,  1), // Top left
-    ( 0,  1), // Top
-    ( 1,  1), // TOp right
-];
-
 /// Base tile map
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Resource)]
 pub struct TileMap {
     bomb_count: u16,
     height: u16,
     width: u16,
+    topology: Topology,
+    /// Do the edges wrap around, left neighboring right and bottom neighboring top
+    wrap: bool,
+    /// Neighbors counted on square grids
+    neighborhood: NeighborhoodRule,
+    /// Most bombs placed in a tile, `Tile::MAX_MINES` at most
+    max_mines: u8,
     map: Vec<Vec<Tile>>,
 }
 
 impl TileMap {
+    /// Largest width or height of a loaded layout, the most the MBF format stores
+    pub const MAX_LAYOUT_SIDE: u16 = u8::MAX as u16;
+
     /// Generates an empty map
     pub fn empty(width: u16, height: u16) -> Self {
         let map = (0..height)
-            .into_iter()
-            .map(|_| (0..width).into_iter().map(|_| Tile::Empty).collect())
+            .map(|_| (0..width).map(|_| Tile::Empty).collect())
             .collect();
         Self {
             bomb_count: 0,
             height,
             width,
+            topology: Topology::Square,
+            wrap: false,
+            neighborhood: NeighborhoodRule::Adjacent,
+            max_mines: 1,
             map,
         }
     }
 
+    /// Uses the `topology` grid, counting the bomb neighbors again
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self.set_bomb_neighbors();
+        self
+    }
+
+    /// Makes the edges wrap around or not, counting the bomb neighbors again
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self.set_bomb_neighbors();
+        self
+    }
+
+    /// Counts the neighbors of the `rule` on square grids, counting the bomb neighbors again
+    pub fn with_neighborhood(mut self, rule: NeighborhoodRule) -> Self {
+        self.neighborhood = rule;
+        self.set_bomb_neighbors();
+        self
+    }
+
+    /// Lets `set_bombs` place up to `max_mines` bombs in a tile
+    pub fn with_max_mines(mut self, max_mines: u8) -> Self {
+        self.max_mines = max_mines.clamp(1, Tile::MAX_MINES);
+        self
+    }
+
+    /// Generates a map with bombs at the given coordinates
+    pub fn from_mines(width: u16, height: u16, mines: &[Coordinates]) -> Result<Self, LayoutError> {
+        Self::place_mines(width, height, mines, 1)
+    }
+
+    /// Generates a multi-mine map, a tile listed several times holds as many bombs
+    pub fn from_multi_mines(width: u16, height: u16, mines: &[Coordinates]) -> Result<Self, LayoutError> {
+        Self::place_mines(width, height, mines, Tile::MAX_MINES)
+    }
+
+    fn place_mines(width: u16, height: u16, mines: &[Coordinates], max_mines: u8) -> Result<Self, LayoutError> {
+        // Checked before allocating, the size comes from the layout file
+        if width > Self::MAX_LAYOUT_SIDE || height > Self::MAX_LAYOUT_SIDE {
+            return Err(LayoutError::TooLarge { width, height });
+        }
+        let mut tile_map = Self::empty(width, height).with_max_mines(max_mines);
+        for coords in mines {
+            if coords.x >= width || coords.y >= height {
+                return Err(LayoutError::MineOutOfBounds(*coords));
+            }
+            let tile = &mut tile_map[coords.y as usize][coords.x as usize];
+            if tile.mines() >= max_mines {
+                return Err(LayoutError::DuplicateMine(*coords));
+            }
+            *tile = Tile::Bomb(tile.mines() + 1);
+        }
+        tile_map.bomb_count = mines.len() as u16;
+        tile_map.set_bomb_neighbors();
+        Ok(tile_map)
+    }
+
     #[cfg(feature = "debug")]
     pub fn console_output(&self) -> String {
         let mut buffer = format!(
             "Map ({}, {}) with {} bombs:\n",
             self.width, self.height, self.bomb_count
         );
-        let line: String = (0..=(self.width + 1)).into_iter().map(|_| '-').collect();
+        let line: String = (0..=(self.width + 1)).map(|_| '-').collect();
         buffer = format!("{}{}\n", buffer, line);
         for line in self.iter().rev() {
             buffer = format!("{}|", buffer);
@@ -66,16 +128,67 @@ impl TileMap {
         self.height
     }
 
-    // Getter for 'bomb_count'
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Neighbors counted by the numbers, hexagonal grids ignore the rule they were given
+    pub fn neighborhood(&self) -> NeighborhoodRule {
+        match self.topology {
+            Topology::Square => self.neighborhood,
+            Topology::Hexagonal => NeighborhoodRule::Adjacent,
+        }
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    /// Do the bottom and top edges wrap around. The shifted rows of hexagonal maps
+    /// only alternate across them with an even height, otherwise only left and right wrap
+    pub fn wraps_vertically(&self) -> bool {
+        self.wrap && (self.topology != Topology::Hexagonal || self.height.is_multiple_of(2))
+    }
+
+    /// Most bombs a tile can hold, also the most flags a tile can take
+    pub fn max_mines(&self) -> u8 {
+        self.max_mines
+    }
+
+    // Getter for 'bomb_count', multi-mine tiles count all their bombs
     pub fn bomb_count(&self) -> u16 {
         self.bomb_count
     }
 
+    /// Number of tiles holding bombs, the ones left covered on a completed board
+    pub fn bomb_tile_count(&self) -> usize {
+        self.iter().flatten().filter(|tile| tile.is_bomb()).count()
+    }
+
+    /// Neighbors of the tile at `coordinates` inside the map, across the edges if they wrap
     pub fn safe_square_at(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> {
-        SQUARE_COORDINATES
+        let size = (self.width, self.height);
+        let mut neighbors: Vec<Coordinates> = self
+            .topology
+            .neighbor_deltas(coordinates, self.neighborhood)
             .iter()
-            .copied()
-            .map(move |tuple| coordinates + tuple)
+            .filter_map(|delta| {
+                if !self.wrap {
+                    return coordinates.checked_add(*delta);
+                }
+                let vertical = coordinates.checked_add((0, delta.1));
+                if vertical.is_none_or(|c| c.y >= self.height) && !self.wraps_vertically() {
+                    return None;
+                }
+                Some(coordinates.wrapping_add(*delta, size))
+            })
+            .filter(|c| c.x < self.width && c.y < self.height)
+            // Small wrapped maps reach the same tile from several sides
+            .filter(|c| *c != coordinates)
+            .collect();
+        neighbors.sort();
+        neighbors.dedup();
+        neighbors.into_iter()
     }
 
     pub fn is_bomb_at(&self, coordinates: Coordinates) -> bool {
@@ -85,22 +198,34 @@ impl TileMap {
         return self.map[coordinates.y as usize][coordinates.x as usize].is_bomb();
     }
 
+    /// Number of bombs in the tile at `coordinates`
+    pub fn mines_at(&self, coordinates: Coordinates) -> u8 {
+        if coordinates.x >= self.width || coordinates.y >= self.height {
+            return 0;
+        }
+        self.map[coordinates.y as usize][coordinates.x as usize].mines()
+    }
+
+    /// Number of bombs around the tile at `coordinates`, summing multi-mine tiles
     pub fn bomb_count_at(&self, coordinates: Coordinates) -> u8 {
         if self.is_bomb_at(coordinates) {
             return 0;
         }
-        let res = self
-            .safe_square_at(coordinates)
-            .filter(|coord| self.is_bomb_at(*coord))
-            .count();
-        res as u8
+        self.safe_square_at(coordinates)
+            .map(|coord| self.mines_at(coord))
+            .sum()
     }
 
     /// Places bombs and bomb neighbor tiles
     pub fn set_bombs(&mut self, bomb_count: u16) {
+        self.set_bombs_with(bomb_count, &mut thread_rng());
+    }
+
+    /// Places bombs drawn from `rng` and bomb neighbor tiles. A bomb drawn on a bomb tile
+    /// joins it, up to `max_mines`
+    pub fn set_bombs_with(&mut self, bomb_count: u16, rng: &mut impl Rng) {
         self.bomb_count = bomb_count;
         let mut remaining_bombs = bomb_count;
-        let mut rng = thread_rng();
 
         // Place bombs
         while remaining_bombs > 0 {
@@ -108,25 +233,29 @@ impl TileMap {
                 rng.gen_range(0..self.width) as usize,
                 rng.gen_range(0..self.height) as usize,
             );
-            if let Tile::Empty = self[y][x] {
-                self[y][x] = Tile::Bomb;
+            let mines = self[y][x].mines();
+            if mines < self.max_mines {
+                self[y][x] = Tile::Bomb(mines + 1);
                 remaining_bombs -= 1;
             }
         }
 
-        // Place bomb neighbors
+        self.set_bomb_neighbors();
+    }
+
+    /// Places bomb neighbor tiles around the bombs
+    fn set_bomb_neighbors(&mut self) {
         for y in 0..self.height {
             for x in 0..self.width {
                 let coords = Coordinates { x, y };
                 if self.is_bomb_at(coords) {
                     continue;
                 }
-                let num = self.bomb_count_at(coords);
-                if num == 0 {
-                    continue;
-                }
-                let tile = &mut self[y as usize][x as usize];
-                *tile = Tile::BombNeighbor(num);
+                let tile = match self.bomb_count_at(coords) {
+                    0 => Tile::Empty,
+                    num => Tile::BombNeighbor(num),
+                };
+                self[y as usize][x as usize] = tile;
             }
         }
     }
@@ -145,3 +274,92 @@ impl DerefMut for TileMap {
         &mut self.map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn neighbors(tile_map: &TileMap, x: u16, y: u16) -> Vec<Coordinates> {
+        tile_map.safe_square_at(Coordinates { x, y }).collect()
+    }
+
+    #[test]
+    fn neighbors_stop_at_the_edges() {
+        let tile_map = TileMap::empty(4, 3);
+        assert_eq!(neighbors(&tile_map, 0, 0).len(), 3);
+        assert_eq!(neighbors(&tile_map, 3, 1).len(), 5);
+        assert_eq!(neighbors(&tile_map, 1, 1).len(), 8);
+    }
+
+    #[test]
+    fn neighbors_wrap_around_the_edges() {
+        let tile_map = TileMap::empty(4, 3).with_wrap(true);
+        let corner = neighbors(&tile_map, 0, 0);
+        assert_eq!(corner.len(), 8);
+        for (x, y) in [(3, 2), (0, 2), (1, 2), (3, 0), (3, 1)] {
+            assert!(corner.contains(&Coordinates { x, y }), "({}, {}) missing", x, y);
+        }
+
+        // Both sides of a 2 wide map are the same tile, counted once
+        let tile_map = TileMap::empty(2, 3).with_wrap(true);
+        assert_eq!(neighbors(&tile_map, 0, 1).len(), 5);
+
+        let hexagonal = TileMap::empty(4, 4).with_topology(Topology::Hexagonal).with_wrap(true);
+        assert_eq!(neighbors(&hexagonal, 0, 0).len(), 6);
+        // Odd rows would meet across the bottom and top edges
+        let hexagonal = TileMap::empty(4, 5).with_topology(Topology::Hexagonal).with_wrap(true);
+        assert!(!hexagonal.wraps_vertically());
+        assert_eq!(neighbors(&hexagonal, 0, 0).len(), 4);
+        assert_eq!(neighbors(&hexagonal, 0, 1).len(), 6);
+    }
+
+    #[test]
+    fn wrapped_counts_see_across_the_edges() {
+        let bomb = Coordinates { x: 0, y: 0 };
+        let tile_map = TileMap::from_mines(4, 4, &[bomb]).unwrap();
+        assert_eq!(tile_map.bomb_count_at(Coordinates { x: 3, y: 3 }), 0);
+        let tile_map = tile_map.with_wrap(true);
+        assert_eq!(tile_map.bomb_count_at(Coordinates { x: 3, y: 3 }), 1);
+        assert_eq!(tile_map[3][3], Tile::BombNeighbor(1));
+        assert_eq!(tile_map[2][2], Tile::Empty);
+    }
+
+    #[test]
+    fn counts_follow_the_neighborhood_rule() {
+        let bomb = Coordinates { x: 2, y: 2 };
+        let tile_map = TileMap::from_mines(5, 5, &[bomb]).unwrap();
+        let knight = tile_map.clone().with_neighborhood(NeighborhoodRule::Knight);
+        assert_eq!(knight.bomb_count_at(Coordinates { x: 3, y: 3 }), 0);
+        assert_eq!(knight.bomb_count_at(Coordinates { x: 3, y: 4 }), 1);
+        assert_eq!(neighbors(&knight, 0, 0).len(), 2);
+
+        let orthogonal = tile_map.clone().with_neighborhood(NeighborhoodRule::Orthogonal);
+        assert_eq!(orthogonal[3][3], Tile::Empty);
+        assert_eq!(orthogonal[3][2], Tile::BombNeighbor(1));
+
+        let wide = tile_map.with_neighborhood(NeighborhoodRule::Wide);
+        assert!(wide.iter().flatten().all(|t| *t != Tile::Empty));
+        // Hexagonal grids keep their own neighbors
+        let hexagonal = wide.with_topology(Topology::Hexagonal);
+        assert_eq!(hexagonal.neighborhood(), NeighborhoodRule::Adjacent);
+        assert_eq!(neighbors(&hexagonal, 2, 2).len(), 6);
+    }
+
+    #[test]
+    fn multi_mine_tiles_sum_their_bombs() {
+        let mut tile_map = TileMap::empty(3, 3).with_max_mines(Tile::MAX_MINES);
+        tile_map.set_bombs_with(8, &mut StdRng::seed_from_u64(7));
+        assert_eq!(tile_map.bomb_count(), 8);
+        let mines: u16 = tile_map.iter().flatten().map(|t| t.mines() as u16).sum();
+        assert_eq!(mines, 8);
+        assert!(tile_map.iter().flatten().all(|t| t.mines() <= Tile::MAX_MINES));
+        assert!(tile_map.bomb_tile_count() < 8);
+
+        let mut tile_map = TileMap::from_mines(3, 1, &[Coordinates { x: 0, y: 0 }]).unwrap();
+        tile_map[0][2] = Tile::Bomb(3);
+        tile_map.set_bomb_neighbors();
+        assert_eq!(tile_map[0][1], Tile::BombNeighbor(4));
+    }
+}
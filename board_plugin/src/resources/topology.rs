@@ -0,0 +1,190 @@
+use std::fmt::{self, Display, Formatter};
+use bevy::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+use crate::components::Coordinates;
+use crate::resources::NeighborhoodRule;
+
+/// Delta coordinates for the 6 hexagon neighbors of a tile on an even row
+const HEX_EVEN_ROW_COORDINATES: [(i8, i8); 6] = [
+    (-1, -1), // Bottom left
+    ( 0, -1), // Bottom right
+    (-1,  0), // Left
+    ( 1,  0), // Right
+    (-1,  1), // Top left
+    ( 0,  1), // Top right
+];
+
+/// Delta coordinates for the 6 hexagon neighbors of a tile on an odd row, shifted right
+const HEX_ODD_ROW_COORDINATES: [(i8, i8); 6] = [
+    ( 0, -1), // Bottom left
+    ( 1, -1), // Bottom right
+    (-1,  0), // Left
+    ( 1,  0), // Right
+    ( 0,  1), // Top left
+    ( 1,  1), // Top right
+];
+
+/// Shape of the grid: which tiles neighbor each other and where tiles are laid out.
+/// Supporting a new grid means adding a variant and its arm in every method
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    /// Square tiles with 8 neighbors
+    #[default]
+    Square,
+    /// Pointy-top hexagons with 6 neighbors, odd rows are shifted right by half a tile
+    Hexagonal,
+}
+
+impl Topology {
+    pub const ALL: [Self; 2] = [Self::Square, Self::Hexagonal];
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Delta coordinates of the neighbors of the tile at `coords`, square grids count
+    /// the ones of `rule`
+    pub fn neighbor_deltas(&self, coords: Coordinates, rule: NeighborhoodRule) -> &'static [(i8, i8)] {
+        match self {
+            Self::Square => rule.deltas(),
+            Self::Hexagonal if coords.y.is_multiple_of(2) => &HEX_EVEN_ROW_COORDINATES,
+            Self::Hexagonal => &HEX_ODD_ROW_COORDINATES,
+        }
+    }
+
+    /// Vertical distance between the centers of two rows of tiles `tile_size` wide
+    fn row_height(&self, tile_size: f32) -> f32 {
+        match self {
+            Self::Square => tile_size,
+            Self::Hexagonal => tile_size * 3f32.sqrt() / 2.,
+        }
+    }
+
+    /// Sprite size of a tile `tile_size` wide
+    pub fn tile_sprite_size(&self, tile_size: f32) -> Vec2 {
+        match self {
+            Self::Square => Vec2::splat(tile_size),
+            // Point to point height of the hexagon
+            Self::Hexagonal => Vec2::new(tile_size, tile_size * 2. / 3f32.sqrt()),
+        }
+    }
+
+    /// Center of the tile at `coords`, from the bottom left corner of the board
+    pub fn tile_center(&self, coords: Coordinates, tile_size: f32) -> Vec2 {
+        let shift = match self {
+            Self::Hexagonal if !coords.y.is_multiple_of(2) => tile_size / 2.,
+            _ => 0.,
+        };
+        Vec2::new(
+            coords.x as f32 * tile_size + tile_size / 2. + shift,
+            coords.y as f32 * self.row_height(tile_size) + self.tile_sprite_size(tile_size).y / 2.,
+        )
+    }
+
+    /// Size of a board of `width` x `height` tiles
+    pub fn board_size(&self, (width, height): (u16, u16), tile_size: f32) -> Vec2 {
+        match self {
+            Self::Square => Vec2::new(width as f32, height as f32) * tile_size,
+            Self::Hexagonal => {
+                let shift = if height > 1 { tile_size / 2. } else { 0. };
+                Vec2::new(
+                    width as f32 * tile_size + shift,
+                    height.saturating_sub(1) as f32 * self.row_height(tile_size)
+                        + self.tile_sprite_size(tile_size).y,
+                )
+            }
+        }
+    }
+
+    /// Tile of a `width` x `height` board under `position`, from the bottom left corner of the board
+    pub fn tile_at(&self, position: Vec2, (width, height): (u16, u16), tile_size: f32) -> Option<Coordinates> {
+        if position.x < 0. || position.y < 0. {
+            return None;
+        }
+        let coords = match self {
+            Self::Square => Coordinates {
+                x: (position.x / tile_size) as u16,
+                y: (position.y / tile_size) as u16,
+            },
+            Self::Hexagonal => {
+                // The hexagon containing a point is the one with the closest center
+                let row = (position.y / self.row_height(tile_size)) as i32;
+                let column = (position.x / tile_size) as i32;
+                let candidates = (row - 1..=row + 1)
+                    .flat_map(|y| (column - 1..=column + 1).map(move |x| (x, y)))
+                    .filter(|(x, y)| *x >= 0 && *y >= 0)
+                    .map(|(x, y)| Coordinates { x: x as u16, y: y as u16 });
+                let distance = |c: &Coordinates| self.tile_center(*c, tile_size).distance_squared(position);
+                let closest = candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))?;
+                // Points between the hexagons on the edges of the board are outside of them
+                let offset = (position - self.tile_center(closest, tile_size)).abs();
+                let radius = tile_size / 3f32.sqrt();
+                if offset.x > tile_size / 2. || offset.y > radius - offset.x / 3f32.sqrt() {
+                    return None;
+                }
+                closest
+            }
+        };
+        (coords.x < width && coords.y < height).then_some(coords)
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Square => write!(f, "square"),
+            Self::Hexagonal => write!(f, "hexagonal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexagon_neighbors_are_mutual() {
+        let topology = Topology::Hexagonal;
+        for y in 2..4 {
+            let coords = Coordinates { x: 2, y };
+            let neighbors: Vec<Coordinates> = topology
+                .neighbor_deltas(coords, NeighborhoodRule::Adjacent)
+                .iter()
+                .filter_map(|d| coords.checked_add(*d))
+                .collect();
+            assert_eq!(neighbors.len(), 6);
+            for neighbor in neighbors {
+                let back: Vec<Coordinates> = topology
+                    .neighbor_deltas(neighbor, NeighborhoodRule::Adjacent)
+                    .iter()
+                    .filter_map(|d| neighbor.checked_add(*d))
+                    .collect();
+                assert!(back.contains(&coords), "{} doesn't neighbor {}", neighbor, coords);
+                // Neighbors touch, their centers are one tile apart
+                let distance = topology.tile_center(coords, 1.).distance(topology.tile_center(neighbor, 1.));
+                assert!((distance - 1.).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn picks_the_tile_under_the_position() {
+        for topology in Topology::ALL {
+            let size = (5, 4);
+            for y in 0..size.1 {
+                for x in 0..size.0 {
+                    let coords = Coordinates { x, y };
+                    let center = topology.tile_center(coords, 10.);
+                    assert_eq!(topology.tile_at(center, size, 10.), Some(coords));
+                    assert_eq!(topology.tile_at(center + Vec2::new(4., 0.), size, 10.), Some(coords));
+                }
+            }
+            let board = topology.board_size(size, 10.);
+            assert_eq!(topology.tile_at(board + 1., size, 10.), None);
+            assert_eq!(topology.tile_at(Vec2::new(-1., 5.), size, 10.), None);
+        }
+        // Left of the first shifted row
+        assert_eq!(Topology::Hexagonal.tile_at(Vec2::new(2., 14.), (5, 4), 10.), None);
+    }
+}
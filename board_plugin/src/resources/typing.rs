@@ -0,0 +1,6 @@
+use bevy::prelude::Resource;
+
+/// Present while the player types text, like a high score name, so the board keys are
+/// left to the text field
+#[derive(Debug, Copy, Clone, Default, Resource)]
+pub struct Typing;
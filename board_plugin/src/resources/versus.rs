@@ -0,0 +1,103 @@
+use std::fmt::{self, Display, Formatter};
+use bevy::prelude::Resource;
+
+/// Player of a hot-seat versus game
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+impl Player {
+    pub const ALL: [Self; 2] = [Self::One, Self::Two];
+
+    /// The other player
+    pub fn next(&self) -> Self {
+        match self {
+            Self::One => Self::Two,
+            Self::Two => Self::One,
+        }
+    }
+
+    /// Index of the player in `VersusGame::scores` and `BoardAssets::player_flag_colors`
+    pub fn index(&self) -> usize {
+        match self {
+            Self::One => 0,
+            Self::Two => 1,
+        }
+    }
+}
+
+impl Display for Player {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Player {}", self.index() + 1)
+    }
+}
+
+/// Turn and scores of a versus game, two players taking turns on the same board
+#[derive(Debug, Clone, Default, Resource)]
+pub struct VersusGame {
+    /// Player whose turn it is
+    pub turn: Player,
+    /// Mines claimed by each player
+    pub scores: [u16; 2],
+    pub over: bool,
+}
+
+impl VersusGame {
+    pub fn score(&self, player: Player) -> u16 {
+        self.scores[player.index()]
+    }
+
+    /// Scores `mines` claimed by `player`
+    pub fn claim(&mut self, player: Player, mines: u16) {
+        self.scores[player.index()] += mines;
+    }
+
+    pub fn pass_turn(&mut self) {
+        self.turn = self.turn.next();
+    }
+
+    /// Is the game decided on a board of `total` mines: a player claimed more than half
+    /// of them, or none is left
+    pub fn is_decided(&self, total: u16) -> bool {
+        let [one, two] = self.scores;
+        one * 2 > total || two * 2 > total || one + two >= total
+    }
+
+    /// Player with the higher score, `None` for a draw
+    pub fn leader(&self) -> Option<Player> {
+        let [one, two] = self.scores;
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => Some(Player::One),
+            std::cmp::Ordering::Less => Some(Player::Two),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn majority_decides_the_game() {
+        let mut game = VersusGame::default();
+        game.claim(Player::One, 2);
+        game.pass_turn();
+        game.claim(game.turn, 2);
+        assert_eq!(game.turn, Player::Two);
+        assert!(!game.is_decided(5));
+        assert_eq!(game.leader(), None);
+
+        game.claim(Player::Two, 1);
+        assert!(game.is_decided(5));
+        assert_eq!(game.leader(), Some(Player::Two));
+        // Even totals can end in a draw once every mine is claimed
+        let game = VersusGame { scores: [2, 2], ..Default::default() };
+        assert!(game.is_decided(4));
+        assert_eq!(game.leader(), None);
+    }
+}
@@ -0,0 +1,7 @@
+/// SplitMix64 finalizer, close inputs like consecutive days or neighboring chunks get
+/// unrelated seeds
+pub(crate) fn splitmix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use bevy::prelude::{Entity, Vec2};
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::bounds::Bounds2;
+use crate::components::Coordinates;
+use crate::resources::{Board, BoardMetrics, BoardOptions, GameMode, Tile, TileMap};
+use crate::solver::{guess, local_moves, solved_moves, supports, BoardView, HintKind};
+
+/// Outcome of a single bot game
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct GameReport {
+    pub won: bool,
+    /// Tiles uncovered or flagged by the bot
+    pub moves: u32,
+    /// Uncovered tiles which weren't certainly safe
+    pub guesses: u32,
+    /// Steps where only the exact solver found a move
+    pub solver_steps: u32,
+}
+
+/// Outcome of a series of bot games
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct BotReport {
+    pub games: u32,
+    pub wins: u32,
+    pub guesses: u32,
+    pub elapsed: Duration,
+}
+
+impl BotReport {
+    /// Share of games won, between 0 and 1
+    pub fn win_rate(&self) -> f32 {
+        if self.games == 0 {
+            return 0.;
+        }
+        self.wins as f32 / self.games as f32
+    }
+
+    /// Average number of guesses per game
+    pub fn guesses_per_game(&self) -> f32 {
+        if self.games == 0 {
+            return 0.;
+        }
+        self.guesses as f32 / self.games as f32
+    }
+}
+
+/// Plays `games` random boards generated from `options` and `seed`.
+///
+/// Only `map_size`, `bomb_count`, `safe_start`, `lives` and `mode` are used, the layout
+/// file is ignored.
+pub fn play_games(options: &BoardOptions, seed: u64, games: u32) -> BotReport {
+    let start = Instant::now();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = BotReport::default();
+    for _ in 0..games {
+        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
+        tile_map.set_bombs_with(options.bomb_count, &mut rng);
+        let board = headless_board(tile_map, options.lives, options.mode);
+        let game = play(board, options.safe_start, false);
+        report.games += 1;
+        report.wins += game.won as u32;
+        report.guesses += game.guesses;
+    }
+    report.elapsed = start.elapsed();
+    report
+}
+
+/// Plays a board until it is completed or a bomb explodes
+pub fn play_game(tile_map: TileMap, safe_start: bool) -> GameReport {
+    play(headless_board(tile_map, None, GameMode::Classic), safe_start, false)
+}
+
+/// Solver based difficulty of a board: a point per guess the bot needs and a tenth
+/// per step needing the exact solver. Bombs hit by guesses are flagged instead of
+/// exploding so the whole board is scored. Boards the solver doesn't support score 0.
+pub fn difficulty(tile_map: &TileMap, safe_start: bool) -> f32 {
+    if !supports(tile_map) {
+        return 0.;
+    }
+    let report = play(headless_board(tile_map.clone(), None, GameMode::Classic), safe_start, true);
+    report.guesses as f32 + report.solver_steps as f32 / 10.
+}
+
+/// Plays a board, with an `oracle` the bombs hit by guesses are flagged instead
+fn play(mut board: Board, safe_start: bool, oracle: bool) -> GameReport {
+    let mut report = GameReport::default();
+
+    if safe_start {
+        // Same tile as the one `BoardPlugin` uncovers
+        let first_empty = board.tile_map.iter().enumerate().find_map(|(y, line)| {
+            line.iter()
+                .position(|t| *t == Tile::Empty)
+                .map(|x| Coordinates { x: x as u16, y: y as u16 })
+        });
+        if let Some(coords) = first_empty {
+            uncover(&mut board, coords);
+        }
+    }
+
+    while !board.is_completed() {
+        let view = BoardView::from_board(&board);
+        let mut moves = local_moves(&view, &board.marked_tiles);
+        if moves.is_empty() {
+            moves = solved_moves(&view, &board.marked_tiles);
+            report.solver_steps += !moves.is_empty() as u32;
+        }
+        if moves.is_empty() {
+            match guess(&view, &board.marked_tiles) {
+                Some(h) => moves.push(h),
+                None => break,
+            }
+        }
+        for hint in moves {
+            report.moves += 1;
+            match hint.kind {
+                HintKind::Mine => {
+                    board.try_toggle_mark(&hint.coordinates);
+                }
+                HintKind::Guess if oracle && board.tile_map.is_bomb_at(hint.coordinates) => {
+                    report.guesses += 1;
+                    board.try_toggle_mark(&hint.coordinates);
+                }
+                HintKind::Safe | HintKind::Guess => {
+                    report.guesses += (hint.kind == HintKind::Guess) as u32;
+                    if !uncover(&mut board, hint.coordinates) {
+                        return report;
+                    }
+                }
+            }
+        }
+    }
+    report.won = board.is_completed();
+    report
+}
+
+/// Board without any spawned entity, with placeholder covers
+fn headless_board(tile_map: TileMap, lives: Option<u8>, mode: GameMode) -> Board {
+    let covered_tiles: HashMap<Coordinates, Entity> = (0..tile_map.height())
+        .flat_map(|y| (0..tile_map.width()).map(move |x| Coordinates { x, y }))
+        .map(|c| (c, Entity::from_raw(u32::MAX)))
+        .collect();
+    Board {
+        bounds: Bounds2 {
+            position: Vec2::ZERO,
+            size: Vec2::ZERO,
+        },
+        tile_map,
+        tile_size: 1.,
+        covered_tiles,
+        entity: Entity::from_raw(u32::MAX),
+        marked_tiles: Vec::new(),
+        hints_used: 0,
+        clicks: 0,
+        overlay_used: false,
+        metrics: BoardMetrics::default(),
+        seed: None,
+        history: Default::default(),
+        game_over: false,
+        lives,
+        detonated: Vec::new(),
+        mode,
+    }
+}
+
+/// Uncovers a tile with the board rules, returns `false` if it ended the game
+fn uncover(board: &mut Board, coords: Coordinates) -> bool {
+    if board.tile_to_uncover(&coords).is_none() {
+        return true;
+    }
+    let mut queue = VecDeque::from([coords]);
+    while let Some(coords) = queue.pop_front() {
+        queue.extend(board.reveal(coords).unwrap_or_default());
+    }
+    !board.is_exploded()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wins_without_guessing_from_safe_start() {
+        let tile_map = TileMap::from_ascii("..1*\n..11\n11..\n*1..").unwrap();
+        let report = play_game(tile_map, true);
+        assert!(report.won);
+        assert_eq!(report.guesses, 0);
+    }
+
+    #[test]
+    fn loses_on_a_bomb() {
+        // Every tile is a bomb but one, the first guess is deadly
+        let tile_map = TileMap::from_ascii("**\n*.").unwrap();
+        let report = play_game(tile_map, false);
+        assert!(!report.won);
+        assert_eq!(report.guesses, 1);
+    }
+
+    #[test]
+    fn plays_through_lives() {
+        // Every wrong guess costs a life instead of ending the game
+        let tile_map = TileMap::from_ascii("**\n*.").unwrap();
+        let report = play(headless_board(tile_map, Some(5), GameMode::Classic), false, false);
+        assert!(report.won);
+        assert!(report.guesses >= 1);
+    }
+
+    #[test]
+    fn difficulty_counts_guesses() {
+        let tile_map = TileMap::from_ascii("..1*\n..11\n11..\n*1..").unwrap();
+        assert_eq!(difficulty(&tile_map, true), 0.);
+        // Nothing can be deduced before the safe tile is found, but the oracle finishes the board
+        let tile_map = TileMap::from_ascii("**\n*.").unwrap();
+        assert!(difficulty(&tile_map, false) >= 1.);
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        let options = BoardOptions {
+            map_size: (9, 9),
+            bomb_count: 10,
+            safe_start: true,
+            ..Default::default()
+        };
+        let first = play_games(&options, 42, 50);
+        let second = play_games(&options, 42, 50);
+        assert_eq!(first.games, 50);
+        assert_eq!((first.wins, first.guesses), (second.wins, second.guesses));
+        // Beginner boards are mostly solvable
+        assert!(first.win_rate() > 0.5);
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt::{self, Display, Formatter};
+use crate::components::Coordinates;
+use crate::solver::{probabilities, BoardView, Constraint};
+
+/// Probabilities closer than this to 0 or 1 are treated as certain
+const EPSILON: f32 = 1e-6;
+
+/// What a hint suggests doing with a tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HintKind {
+    /// The tile is certainly safe
+    Safe,
+    /// The tile is certainly a mine
+    Mine,
+    /// No certain move exists, the tile is the least likely to be a mine
+    Guess,
+}
+
+/// Move suggested by the deduction engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub coordinates: Coordinates,
+    pub kind: HintKind,
+    /// Constraints proving the move, empty for guesses or when only the bomb count does
+    pub proof: Vec<Constraint>,
+    /// Mine probability of the tile
+    pub probability: f32,
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            HintKind::Safe => write!(f, "{} is safe", self.coordinates)?,
+            HintKind::Mine => write!(f, "{} is a mine", self.coordinates)?,
+            HintKind::Guess => {
+                return write!(
+                    f,
+                    "no certain move, {} has the lowest risk ({:.0}%)",
+                    self.coordinates,
+                    self.probability * 100.
+                );
+            }
+        }
+        for (i, constraint) in self.proof.iter().enumerate() {
+            let separator = if i == 0 { " because" } else { " and" };
+            write!(
+                f,
+                "{} {} has {} mine(s) among {} covered tile(s)",
+                separator,
+                constraint.tile,
+                constraint.mines,
+                constraint.cells.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Hint {
+    fn certain(coordinates: Coordinates, mine: bool, proof: Vec<Constraint>) -> Self {
+        Self {
+            coordinates,
+            kind: if mine { HintKind::Mine } else { HintKind::Safe },
+            proof,
+            probability: if mine { 1. } else { 0. },
+        }
+    }
+}
+
+/// Every move following logically from the view, except flagging the `marked` tiles.
+///
+/// The local rules are tried first as they give the shortest proofs, then the
+/// exact probabilities.
+pub fn forced_moves(view: &BoardView, marked: &[Coordinates]) -> Vec<Hint> {
+    let moves = local_moves(view, marked);
+    if !moves.is_empty() {
+        return moves;
+    }
+    solved_moves(view, marked)
+}
+
+/// Moves proven by a single constraint or by a pair of nested constraints
+pub fn local_moves(view: &BoardView, marked: &[Coordinates]) -> Vec<Hint> {
+    let constraints = view.constraints();
+    let mut moves: Vec<Hint> = Vec::new();
+
+    for constraint in &constraints {
+        let mine = if constraint.mines == 0 {
+            false
+        } else if constraint.mines as usize == constraint.cells.len() {
+            true
+        } else {
+            continue;
+        };
+        for cell in &constraint.cells {
+            push(&mut moves, marked, Hint::certain(*cell, mine, vec![constraint.clone()]));
+        }
+    }
+
+    for inner in &constraints {
+        for outer in &constraints {
+            if inner.cells.len() >= outer.cells.len()
+                || !inner.cells.iter().all(|c| outer.cells.contains(c))
+                || outer.mines < inner.mines
+            {
+                continue;
+            }
+            let rest: Vec<Coordinates> = outer
+                .cells
+                .iter()
+                .filter(|c| !inner.cells.contains(c))
+                .copied()
+                .collect();
+            let mines = (outer.mines - inner.mines) as usize;
+            if mines != 0 && mines != rest.len() {
+                continue;
+            }
+            for cell in rest {
+                let proof = vec![inner.clone(), outer.clone()];
+                push(&mut moves, marked, Hint::certain(cell, mines != 0, proof));
+            }
+        }
+    }
+
+    moves
+}
+
+/// Moves proven by the exact probabilities, with the constraints around the tile
+pub fn solved_moves(view: &BoardView, marked: &[Coordinates]) -> Vec<Hint> {
+    let mut moves = Vec::new();
+    let probabilities = probabilities(view);
+    if !probabilities.is_exact() {
+        return moves;
+    }
+    let constraints = view.constraints();
+    let mut certain: Vec<(Coordinates, f32)> = probabilities
+        .iter()
+        .filter(|(_, p)| *p < EPSILON || *p > 1. - EPSILON)
+        .collect();
+    certain.sort_by_key(|(c, _)| *c);
+    for (cell, p) in certain {
+        let proof = constraints
+            .iter()
+            .filter(|c| c.cells.contains(&cell))
+            .cloned()
+            .collect();
+        push(&mut moves, marked, Hint::certain(cell, p > 0.5, proof));
+    }
+    moves
+}
+
+/// Adds `hint` unless its tile already has a move or is an already marked mine
+fn push(moves: &mut Vec<Hint>, marked: &[Coordinates], hint: Hint) {
+    if hint.kind == HintKind::Mine && marked.contains(&hint.coordinates) {
+        return;
+    }
+    if !moves.iter().any(|m| m.coordinates == hint.coordinates) {
+        moves.push(hint);
+    }
+}
+
+/// The next move to make: a certain one if any, else the lowest risk guess.
+///
+/// Mines already in `marked` aren't hinted again and marked tiles aren't guessed.
+pub fn hint(view: &BoardView, marked: &[Coordinates]) -> Option<Hint> {
+    if let Some(forced) = forced_moves(view, marked).into_iter().next() {
+        return Some(forced);
+    }
+    guess(view, marked)
+}
+
+/// The covered tile least likely to be a mine, outside of `marked`
+pub fn guess(view: &BoardView, marked: &[Coordinates]) -> Option<Hint> {
+    probabilities(view)
+        .iter()
+        .filter(|(c, _)| !marked.contains(c))
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(coordinates, probability)| Hint {
+            coordinates,
+            kind: HintKind::Guess,
+            proof: Vec::new(),
+            probability,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::TileMap;
+
+    /// Every tile of `tile_map` but `revealed`
+    fn view<'a>(tile_map: &'a TileMap, revealed: &[(u16, u16)]) -> BoardView<'a> {
+        let covered = (0..tile_map.height())
+            .flat_map(|y| (0..tile_map.width()).map(move |x| Coordinates { x, y }))
+            .filter(|c| !revealed.contains(&(c.x, c.y)));
+        BoardView::new(tile_map, covered)
+    }
+
+    #[test]
+    fn single_constraint_flags_mine() {
+        // The `1` in the corner only touches the mine
+        let tile_map = TileMap::from_ascii("1*\n11").unwrap();
+        let view = view(&tile_map, &[(0, 0), (1, 0), (0, 1)]);
+        let hint = hint(&view, &[]).unwrap();
+        assert_eq!(hint.kind, HintKind::Mine);
+        assert_eq!(hint.coordinates, Coordinates { x: 1, y: 1 });
+        assert_eq!(hint.proof.len(), 1);
+        assert!(hint.to_string().starts_with("(1, 1) is a mine because"));
+    }
+
+    #[test]
+    fn nested_constraints_prove_safe_tile() {
+        // Bottom row `1 1 .`: the left `1` covers (0, 1) and (1, 1), the middle
+        // one also covers (2, 1), which must then be safe
+        let tile_map = TileMap::from_ascii("*..\n11.").unwrap();
+        let view = view(&tile_map, &[(0, 0), (1, 0)]);
+        let moves = forced_moves(&view, &[]);
+        let safe = moves
+            .iter()
+            .find(|h| h.coordinates == Coordinates { x: 2, y: 1 })
+            .unwrap();
+        assert_eq!(safe.kind, HintKind::Safe);
+        assert_eq!(safe.proof.len(), 2);
+    }
+
+    #[test]
+    fn skips_marked_mines_and_guesses() {
+        let tile_map = TileMap::from_ascii("1*\n11").unwrap();
+        let corner = view(&tile_map, &[(0, 0), (1, 0), (0, 1)]);
+        // Nothing is left but the marked mine
+        assert!(hint(&corner, &[Coordinates { x: 1, y: 1 }]).is_none());
+
+        let tile_map = TileMap::from_ascii("*.\n..").unwrap();
+        let hint = hint(&view(&tile_map, &[]), &[Coordinates { x: 0, y: 1 }]).unwrap();
+        assert_eq!(hint.kind, HintKind::Guess);
+        assert_eq!(hint.coordinates, Coordinates { x: 0, y: 0 });
+        assert!((hint.probability - 0.25).abs() < 1e-4);
+    }
+}
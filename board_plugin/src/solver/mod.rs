@@ -0,0 +1,155 @@
+//! Minesweeper reasoning working only from what the player can see
+
+pub use bot::*;
+pub use deduction::*;
+pub use probability::*;
+
+use crate::components::Coordinates;
+use crate::resources::{Board, Tile, TileMap};
+
+mod bot;
+mod deduction;
+mod probability;
+
+/// Can the solver reason about `tile_map`? It assumes a single bomb per tile
+pub fn supports(tile_map: &TileMap) -> bool {
+    tile_map.max_mines() == 1
+}
+
+/// The player's view of a board: revealed tiles and the total bomb count
+#[derive(Debug, Clone)]
+pub struct BoardView<'a> {
+    tile_map: &'a TileMap,
+    covered: Vec<bool>,
+}
+
+/// A revealed number and the covered tiles around it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Constraint {
+    /// The revealed tile
+    pub tile: Coordinates,
+    /// Covered neighbors of `tile`
+    pub cells: Vec<Coordinates>,
+    /// Number of bombs among `cells`
+    pub mines: u8,
+}
+
+impl<'a> BoardView<'a> {
+    /// Builds a view of `tile_map` where only `covered` tiles are hidden
+    pub fn new(tile_map: &'a TileMap, covered: impl IntoIterator<Item = Coordinates>) -> Self {
+        let mut view = Self {
+            tile_map,
+            covered: vec![false; tile_map.width() as usize * tile_map.height() as usize],
+        };
+        for coords in covered {
+            if let Some(i) = view.index(coords) {
+                view.covered[i] = true;
+            }
+        }
+        view
+    }
+
+    /// Builds a view of the covered tiles of `board`
+    pub fn from_board(board: &'a Board) -> Self {
+        Self::new(&board.tile_map, board.covered_tiles.keys().copied())
+    }
+
+    pub fn width(&self) -> u16 {
+        self.tile_map.width()
+    }
+
+    pub fn height(&self) -> u16 {
+        self.tile_map.height()
+    }
+
+    /// Total number of bombs on the board
+    pub fn bomb_count(&self) -> u16 {
+        self.tile_map.bomb_count()
+    }
+
+    fn index(&self, coords: Coordinates) -> Option<usize> {
+        if coords.x >= self.width() || coords.y >= self.height() {
+            return None;
+        }
+        Some(coords.y as usize * self.width() as usize + coords.x as usize)
+    }
+
+    pub fn is_covered(&self, coords: Coordinates) -> bool {
+        self.index(coords).is_some_and(|i| self.covered[i])
+    }
+
+    /// The tile at `coords` if it has been revealed
+    pub fn revealed(&self, coords: Coordinates) -> Option<Tile> {
+        let i = self.index(coords)?;
+        if self.covered[i] {
+            return None;
+        }
+        Some(self.tile_map[coords.y as usize][coords.x as usize])
+    }
+
+    /// Neighbors of `coords` inside the board
+    pub fn neighbors(&self, coords: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+        self.tile_map
+            .safe_square_at(coords)
+            .filter(move |c| self.index(*c).is_some())
+    }
+
+    /// All covered tiles, row by row
+    pub fn covered_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        let width = self.width() as usize;
+        self.covered
+            .iter()
+            .enumerate()
+            .filter(|(_, covered)| **covered)
+            .map(move |(i, _)| Coordinates {
+                x: (i % width) as u16,
+                y: (i / width) as u16,
+            })
+    }
+
+    /// Bombs which are still covered
+    pub fn remaining_bombs(&self) -> u16 {
+        let revealed = self
+            .covered
+            .iter()
+            .enumerate()
+            .filter(|(i, covered)| {
+                let (x, y) = (i % self.width() as usize, i / self.width() as usize);
+                !**covered && self.tile_map[y][x].is_bomb()
+            })
+            .count() as u16;
+        self.bomb_count().saturating_sub(revealed)
+    }
+
+    /// Constraints of every revealed number with covered neighbors
+    pub fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let tile = Coordinates { x, y };
+                let count = match self.revealed(tile) {
+                    Some(Tile::BombNeighbor(v)) => v,
+                    Some(Tile::Empty) => 0,
+                    _ => continue,
+                };
+                let mut cells = Vec::new();
+                let mut known = 0;
+                for neighbor in self.neighbors(tile) {
+                    match self.revealed(neighbor) {
+                        None => cells.push(neighbor),
+                        Some(Tile::Bomb(n)) => known += n,
+                        Some(_) => (),
+                    }
+                }
+                if !cells.is_empty() {
+                    constraints.push(Constraint {
+                        tile,
+                        cells,
+                        mines: count.saturating_sub(known),
+                    });
+                }
+            }
+        }
+        constraints
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::VecDeque;
+use bevy::utils::HashMap;
+use crate::components::Coordinates;
+use crate::solver::BoardView;
+
+/// Maximum number of search nodes spent enumerating a single group of constrained tiles
+const MAX_NODES: usize = 100_000;
+
+/// Mine probability of every covered tile
+#[derive(Debug, Clone, Default)]
+pub struct Probabilities {
+    values: HashMap<Coordinates, f32>,
+    exact: bool,
+    /// Search nodes spent on the groups of constrained tiles
+    nodes: usize,
+}
+
+impl Probabilities {
+    /// Mine probability of a covered tile
+    pub fn get(&self, coords: Coordinates) -> Option<f32> {
+        self.values.get(&coords).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates, f32)> + '_ {
+        self.values.iter().map(|(c, p)| (*c, *p))
+    }
+
+    /// Were all constraints solved within the node budget?
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Search nodes spent enumerating the constrained tiles, all groups together
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Covered tile least likely to be a mine, lowest coordinates first on ties
+    pub fn safest(&self) -> Option<(Coordinates, f32)> {
+        self.iter()
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+    }
+}
+
+/// Solutions of a group of tiles linked by constraints
+struct Group {
+    /// Board tiles of the group
+    cells: Vec<Coordinates>,
+    /// Number of solutions by number of mines in the group
+    weights: Vec<f64>,
+    /// Number of solutions where the tile is a mine, by tile then number of mines
+    mine_weights: Vec<Vec<f64>>,
+}
+
+/// Backtracking state of a group enumeration
+struct Search<'a> {
+    /// Constraint indices of every tile
+    tile_constraints: &'a [Vec<usize>],
+    /// Required mines of every constraint
+    targets: &'a [u8],
+    /// Mines placed around every constraint
+    placed: Vec<u8>,
+    /// Tiles left to decide around every constraint
+    undecided: Vec<u8>,
+    assignment: Vec<bool>,
+    nodes: usize,
+    weights: Vec<f64>,
+    mine_weights: Vec<Vec<f64>>,
+}
+
+impl Search<'_> {
+    /// Enumerates assignments of tiles `i..`, returns `false` when out of budget
+    fn run(&mut self, i: usize, mines: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES {
+            return false;
+        }
+        if i == self.assignment.len() {
+            self.weights[mines] += 1.;
+            for (tile, mine) in self.assignment.iter().enumerate() {
+                if *mine {
+                    self.mine_weights[tile][mines] += 1.;
+                }
+            }
+            return true;
+        }
+        for mine in [false, true] {
+            if self.assign(i, mine) {
+                self.assignment[i] = mine;
+                let in_budget = self.run(i + 1, mines + mine as usize);
+                self.unassign(i, mine);
+                if !in_budget {
+                    return false;
+                }
+            } else {
+                self.unassign(i, mine);
+            }
+        }
+        true
+    }
+
+    /// Decides a tile, returns whether all its constraints can still be met
+    fn assign(&mut self, i: usize, mine: bool) -> bool {
+        let mut valid = true;
+        for c in &self.tile_constraints[i] {
+            self.undecided[*c] -= 1;
+            self.placed[*c] += mine as u8;
+            let target = self.targets[*c];
+            valid &= self.placed[*c] <= target && self.placed[*c] + self.undecided[*c] >= target;
+        }
+        valid
+    }
+
+    fn unassign(&mut self, i: usize, mine: bool) {
+        for c in &self.tile_constraints[i] {
+            self.undecided[*c] += 1;
+            self.placed[*c] -= mine as u8;
+        }
+    }
+}
+
+/// Computes the mine probability of every covered tile.
+///
+/// Constrained tiles are split into independent groups, each enumerated separately,
+/// then combined with the number of ways to place the remaining mines on the
+/// unconstrained tiles. Groups exceeding the node budget are treated as unconstrained.
+pub fn probabilities(view: &BoardView) -> Probabilities {
+    let constraints = view.constraints();
+    let mut index: HashMap<Coordinates, usize> = HashMap::default();
+    let mut cells = Vec::new();
+    for constraint in &constraints {
+        for cell in &constraint.cells {
+            index.entry(*cell).or_insert_with(|| {
+                cells.push(*cell);
+                cells.len() - 1
+            });
+        }
+    }
+    let constraint_cells: Vec<Vec<usize>> = constraints
+        .iter()
+        .map(|c| c.cells.iter().map(|cell| index[cell]).collect())
+        .collect();
+
+    let mut exact = true;
+    let mut nodes = 0;
+    let mut groups = Vec::new();
+    let mut unconstrained: Vec<Coordinates> = view
+        .covered_tiles()
+        .filter(|c| !index.contains_key(c))
+        .collect();
+    for (group_cells, group_constraints) in split_groups(cells.len(), &constraint_cells) {
+        let targets: Vec<u8> = group_constraints.iter().map(|c| constraints[*c].mines).collect();
+        let local: Vec<Vec<usize>> = group_constraints
+            .iter()
+            .map(|c| {
+                constraint_cells[*c]
+                    .iter()
+                    .map(|cell| group_cells.iter().position(|g| g == cell).unwrap())
+                    .collect()
+            })
+            .collect();
+        let coords = group_cells.iter().map(|i| cells[*i]).collect();
+        let (group, group_nodes) = enumerate(coords, &local, &targets);
+        nodes += group_nodes;
+        match group {
+            Some(group) => groups.push(group),
+            None => {
+                exact = false;
+                unconstrained.extend(group_cells.iter().map(|i| cells[*i]));
+            }
+        }
+    }
+
+    let remaining = view.remaining_bombs() as usize;
+    let free = unconstrained.len();
+    let total = groups
+        .iter()
+        .fold(vec![1.], |acc, g| convolve(&acc, &g.weights));
+    let rest = rest_weights(free, remaining, total.len());
+    let norm: f64 = total.iter().zip(&rest).map(|(w, r)| w * r).sum();
+
+    let mut values = HashMap::default();
+    if norm <= 0. {
+        // The view contradicts itself, fall back to the mine density
+        let covered = view.covered_tiles().count().max(1);
+        let density = (remaining as f32 / covered as f32).min(1.);
+        values.extend(view.covered_tiles().map(|c| (c, density)));
+        return Probabilities { values, exact: false, nodes };
+    }
+
+    if free > 0 {
+        let mines: f64 = total
+            .iter()
+            .zip(&rest)
+            .enumerate()
+            .map(|(t, (w, r))| w * r * remaining.saturating_sub(t) as f64)
+            .sum();
+        let p = (mines / free as f64 / norm) as f32;
+        values.extend(unconstrained.iter().map(|c| (*c, p)));
+    }
+    for (g, group) in groups.iter().enumerate() {
+        let others = groups
+            .iter()
+            .enumerate()
+            .filter(|(o, _)| *o != g)
+            .fold(vec![1.], |acc, (_, o)| convolve(&acc, &o.weights));
+        for (cell, mine_weights) in group.cells.iter().zip(&group.mine_weights) {
+            let mut weight = 0.;
+            for (k, w) in mine_weights.iter().enumerate() {
+                for (r, o) in others.iter().enumerate() {
+                    weight += w * o * rest.get(k + r).copied().unwrap_or(0.);
+                }
+            }
+            values.insert(*cell, (weight / norm) as f32);
+        }
+    }
+    Probabilities { values, exact, nodes }
+}
+
+/// Splits tiles into groups linked by shared constraints, with their constraints
+fn split_groups(cell_count: usize, constraint_cells: &[Vec<usize>]) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut parent: Vec<usize> = (0..cell_count).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for cells in constraint_cells {
+        for cell in cells.iter().skip(1) {
+            let (a, b) = (root(&mut parent, cells[0]), root(&mut parent, *cell));
+            parent[a] = b;
+        }
+    }
+
+    let mut groups: Vec<(usize, Vec<usize>, Vec<usize>)> = Vec::new();
+    for (c, cells) in constraint_cells.iter().enumerate() {
+        let r = root(&mut parent, cells[0]);
+        match groups.iter_mut().find(|g| g.0 == r) {
+            Some(group) => group.2.push(c),
+            None => groups.push((r, Vec::new(), vec![c])),
+        }
+    }
+    for cell in 0..cell_count {
+        let r = root(&mut parent, cell);
+        if let Some(group) = groups.iter_mut().find(|g| g.0 == r) {
+            group.1.push(cell);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, cells, constraints)| {
+            let cells = search_order(cells, &constraints, constraint_cells);
+            (cells, constraints)
+        })
+        .collect()
+}
+
+/// Orders tiles so neighbors are decided one after the other, which closes
+/// constraints early and prunes the search
+fn search_order(cells: Vec<usize>, constraints: &[usize], constraint_cells: &[Vec<usize>]) -> Vec<usize> {
+    let mut ordered = Vec::with_capacity(cells.len());
+    let mut queue = VecDeque::from([cells[0]]);
+    while let Some(cell) = queue.pop_front() {
+        if ordered.contains(&cell) {
+            continue;
+        }
+        ordered.push(cell);
+        for c in constraints {
+            if constraint_cells[*c].contains(&cell) {
+                queue.extend(constraint_cells[*c].iter().filter(|n| !ordered.contains(n)));
+            }
+        }
+    }
+    ordered
+}
+
+/// Counts the solutions of a group, `None` when out of budget, with the nodes searched
+fn enumerate(cells: Vec<Coordinates>, constraint_cells: &[Vec<usize>], targets: &[u8]) -> (Option<Group>, usize) {
+    let mut tile_constraints = vec![Vec::new(); cells.len()];
+    for (c, tiles) in constraint_cells.iter().enumerate() {
+        for tile in tiles {
+            tile_constraints[*tile].push(c);
+        }
+    }
+    let mut search = Search {
+        tile_constraints: &tile_constraints,
+        targets,
+        placed: vec![0; targets.len()],
+        undecided: constraint_cells.iter().map(|c| c.len() as u8).collect(),
+        assignment: vec![false; cells.len()],
+        nodes: 0,
+        weights: vec![0.; cells.len() + 1],
+        mine_weights: vec![vec![0.; cells.len() + 1]; cells.len()],
+    };
+    if !search.run(0, 0) {
+        return (None, search.nodes);
+    }
+    let group = Group {
+        cells,
+        weights: search.weights,
+        mine_weights: search.mine_weights,
+    };
+    (Some(group), search.nodes)
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut res = vec![0.; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] += x * y;
+        }
+    }
+    res
+}
+
+/// Ways to place the mines left by `t` constrained mines on the `free` tiles,
+/// for every `t` below `len`, scaled so the largest is 1
+fn rest_weights(free: usize, remaining: usize, len: usize) -> Vec<f64> {
+    let mut ln_factorial = vec![0.; free + 1];
+    for i in 1..=free {
+        ln_factorial[i] = ln_factorial[i - 1] + (i as f64).ln();
+    }
+    let ln_ways: Vec<Option<f64>> = (0..len)
+        .map(|t| {
+            let k = remaining.checked_sub(t)?;
+            if k > free {
+                return None;
+            }
+            Some(ln_factorial[free] - ln_factorial[k] - ln_factorial[free - k])
+        })
+        .collect();
+    let max = ln_ways.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+    ln_ways
+        .iter()
+        .map(|w| w.map_or(0., |w| (w - max).exp()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::TileMap;
+
+    fn assert_near(a: Option<f32>, b: f32) {
+        let a = a.unwrap();
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn one_two_one_pattern() {
+        // Bottom row revealed as 1 2 1, mines above the 1s
+        let tile_map = TileMap::from_ascii("*.*\n121").unwrap();
+        let covered = (0..3).map(|x| Coordinates { x, y: 1 });
+        let probabilities = probabilities(&BoardView::new(&tile_map, covered));
+        assert!(probabilities.is_exact());
+        assert_near(probabilities.get(Coordinates { x: 0, y: 1 }), 1.);
+        assert_near(probabilities.get(Coordinates { x: 1, y: 1 }), 0.);
+        assert_near(probabilities.get(Coordinates { x: 2, y: 1 }), 1.);
+        assert_eq!(probabilities.safest().unwrap().0, Coordinates { x: 1, y: 1 });
+    }
+
+    #[test]
+    fn weighs_groups_with_remaining_mines() {
+        // Only the bottom right `1` and the empty tile next to it are revealed
+        let tile_map = TileMap::from_ascii("..*\n...\n*1.").unwrap();
+        let revealed = [Coordinates { x: 1, y: 0 }, Coordinates { x: 2, y: 0 }];
+        let covered = (0..3)
+            .flat_map(|y| (0..3).map(move |x| Coordinates { x, y }))
+            .filter(|c| !revealed.contains(c));
+        let probabilities = probabilities(&BoardView::new(&tile_map, covered));
+        assert!(probabilities.is_exact());
+        // The empty tile clears its neighbors, so the `1` is on the left column
+        assert_near(probabilities.get(Coordinates { x: 1, y: 1 }), 0.);
+        assert_near(probabilities.get(Coordinates { x: 2, y: 1 }), 0.);
+        assert_near(probabilities.get(Coordinates { x: 0, y: 0 }), 0.5);
+        assert_near(probabilities.get(Coordinates { x: 0, y: 1 }), 0.5);
+        // The other mine is anywhere on the top row
+        assert_near(probabilities.get(Coordinates { x: 0, y: 2 }), 1. / 3.);
+        assert_near(probabilities.get(Coordinates { x: 2, y: 2 }), 1. / 3.);
+    }
+
+    #[test]
+    fn unconstrained_tiles_use_density() {
+        let tile_map = TileMap::from_ascii("*.\n..").unwrap();
+        let covered = (0..2).flat_map(|y| (0..2).map(move |x| Coordinates { x, y }));
+        let probabilities = probabilities(&BoardView::new(&tile_map, covered));
+        for (_, p) in probabilities.iter() {
+            assert!((p - 0.25).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn expert_board_within_node_budget() {
+        let mut mines: Vec<Coordinates> = (0..99)
+            .map(|i| Coordinates { x: (i * 7) % 30, y: (i * 5) % 16 })
+            .collect();
+        mines.sort();
+        mines.dedup();
+        let tile_map = TileMap::from_mines(30, 16, &mines).unwrap();
+        // Reveal a checkerboard on the left half
+        let covered: Vec<Coordinates> = (0..16u16)
+            .flat_map(|y| (0..30u16).map(move |x| Coordinates { x, y }))
+            .filter(|c| c.x >= 15 || (c.x + c.y) % 2 == 0 || tile_map.is_bomb_at(*c))
+            .collect();
+        let probabilities = probabilities(&BoardView::new(&tile_map, covered.iter().copied()));
+        // The node count bounds the work done, whatever the speed of the machine
+        assert!(probabilities.is_exact());
+        assert!(probabilities.nodes() < MAX_NODES / 10, "{} nodes", probabilities.nodes());
+        assert_eq!(probabilities.iter().count(), covered.len());
+        for c in covered.iter().filter(|c| c.x < 14) {
+            let p = probabilities.get(*c).unwrap();
+            assert!(!tile_map.is_bomb_at(*c) || p > 0.);
+        }
+    }
+}
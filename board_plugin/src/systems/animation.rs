@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use crate::{Board, BombExplosionEvent, Tween, TweenKind};
+use crate::resources::AnimationSettings;
+
+/// Color of the flash covering the board on an explosion
+const FLASH_COLOR: Color = Color::rgba(1., 0.3, 0.1, 0.7);
+
+/// Advances every tween, despawning the entities that faded away
+pub fn animate(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tweens: Query<(Entity, &mut Tween, &mut Transform, Option<&mut Sprite>)>,
+) {
+    for (entity, mut tween, mut transform, sprite) in tweens.iter_mut() {
+        tween.elapsed += time.delta_seconds();
+        if tween.elapsed < tween.delay {
+            continue;
+        }
+        let t = tween.progress();
+        match tween.kind {
+            TweenKind::FadeOut { alpha } => {
+                if let Some(mut sprite) = sprite {
+                    sprite.color.set_a(alpha * (1. - t));
+                }
+            }
+            TweenKind::Flip => transform.scale.x = 1. - t,
+            TweenKind::DropIn { rest, height } => {
+                let remaining = 1. - t;
+                transform.translation = rest + Vec3::Y * height * remaining * remaining;
+                transform.scale = Vec3::splat(1. + 0.5 * remaining);
+            }
+            TweenKind::Shake { origin, amplitude } => {
+                let phase = tween.elapsed * 60.;
+                let offset = Vec3::new(phase.sin(), (phase * 1.3).cos(), 0.);
+                transform.translation = origin + offset * amplitude * (1. - t);
+            }
+        }
+        if t < 1. {
+            continue;
+        }
+        match tween.kind {
+            TweenKind::FadeOut { .. } | TweenKind::Flip => {
+                commands.entity(entity).despawn_recursive();
+            }
+            TweenKind::DropIn { .. } | TweenKind::Shake { .. } => {
+                commands.entity(entity).remove::<Tween>();
+            }
+        }
+    }
+}
+
+/// Shakes the board and flashes it on an explosion
+pub fn animate_explosion(
+    mut commands: Commands,
+    board: Res<Board>,
+    settings: Res<AnimationSettings>,
+    transforms: Query<(&Transform, Option<&Tween>)>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+) {
+    if bomb_explosion_evr.iter().count() == 0 || !settings.enabled {
+        return;
+    }
+    let (transform, tween) = match transforms.get(board.entity) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    // Keeps the resting position when explosions chain up
+    let origin = match tween.map(|t| t.kind) {
+        Some(TweenKind::Shake { origin, .. }) => origin,
+        _ => transform.translation,
+    };
+    let shake = TweenKind::Shake {
+        origin,
+        amplitude: settings.shake_amplitude,
+    };
+    let size = board.bounds.size;
+    commands
+        .entity(board.entity)
+        .insert(Tween::new(shake, settings.explosion))
+        .with_children(|parent| {
+            parent
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(size),
+                        color: FLASH_COLOR,
+                        ..Default::default()
+                    },
+                    // Above the tiles, covers and highlights
+                    transform: Transform::from_xyz(size.x / 2., size.y / 2., 5.),
+                    ..Default::default()
+                })
+                .insert(Name::new("Explosion flash"))
+                .insert(Tween::new(TweenKind::FadeOut { alpha: FLASH_COLOR.a() }, settings.explosion));
+        });
+}
@@ -0,0 +1,94 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, Coordinates, HintHighlight};
+use crate::events::{HintEvent, HintRequestEvent, TileMarkEvent, TileTriggerEvent};
+use crate::resources::Topology;
+use crate::solver::{hint, supports, BoardView, HintKind};
+
+const SAFE_COLOR: Color = Color::rgba(0., 1., 0., 0.5);
+const MINE_COLOR: Color = Color::rgba(1., 0., 0., 0.5);
+const GUESS_COLOR: Color = Color::rgba(1., 1., 0., 0.5);
+/// Color of the revealed tiles proving a hint
+const PROOF_COLOR: Color = Color::rgba(0., 0.5, 1., 0.3);
+
+/// Highlights the next logical move when a hint is requested
+pub fn show_hint(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    mut hint_request_rdr: EventReader<HintRequestEvent>,
+    mut hint_wr: EventWriter<HintEvent>,
+    shown: Query<Entity, With<HintHighlight>>,
+) {
+    if hint_request_rdr.iter().count() == 0 {
+        return;
+    }
+    for entity in shown.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !supports(&board.tile_map) {
+        log::info!("No hints on multi-mine boards");
+        return;
+    }
+
+    let hint = match hint(&BoardView::from_board(&board), &board.marked_tiles) {
+        Some(h) => h,
+        None => {
+            log::info!("No hint available");
+            return;
+        }
+    };
+    log::info!("Hint: {}", hint);
+    board.hints_used += 1;
+
+    let color = match hint.kind {
+        HintKind::Safe => SAFE_COLOR,
+        HintKind::Mine => MINE_COLOR,
+        HintKind::Guess => GUESS_COLOR,
+    };
+    let (tile_size, topology) = (board.tile_size, board.tile_map.topology());
+    commands.entity(board.entity).with_children(|parent| {
+        spawn_highlight(parent, hint.coordinates, tile_size, topology, color, "Hint");
+        for constraint in &hint.proof {
+            spawn_highlight(parent, constraint.tile, tile_size, topology, PROOF_COLOR, "Hint proof");
+        }
+    });
+    hint_wr.send(HintEvent(hint));
+}
+
+/// Removes the hint highlights once the player acts
+pub fn clear_hint(
+    mut commands: Commands,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    shown: Query<Entity, With<HintHighlight>>,
+) {
+    if tile_trigger_evr.iter().count() + tile_mark_evr.iter().count() == 0 {
+        return;
+    }
+    for entity in shown.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_highlight(
+    parent: &mut ChildBuilder,
+    coords: Coordinates,
+    size: f32,
+    topology: Topology,
+    color: Color,
+    name: &str,
+) {
+    parent
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                color,
+                ..Default::default()
+            },
+            // Above the tiles and their covers
+            transform: Transform::from_translation(topology.tile_center(coords, size).extend(4.)),
+            ..Default::default()
+        })
+        .insert(Name::new(name.to_string()))
+        .insert(HintHighlight);
+}
@@ -0,0 +1,162 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, BoardAssets, BoardCompletedEvent, BombExplosionEvent, BoardTimer, Coordinates, Detonated, Themed, Tween, Uncover};
+use crate::events::{RedoEvent, TileUncoveredEvent, UndoEvent};
+use crate::resources::{BoardOptions, Change, GenerationMode, Stats};
+use crate::systems::mark::set_flags;
+use crate::systems::uncover::{spawn_cover, spawn_detonated_flags};
+
+/// Reverts the last player action, covering its tiles again and restoring their flags
+#[allow(clippy::too_many_arguments)]
+pub fn undo(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    board_options: Option<Res<BoardOptions>>,
+    board_assets: Res<BoardAssets>,
+    mut timer: ResMut<BoardTimer>,
+    mut stats: ResMut<Stats>,
+    mut undo_evr: EventReader<UndoEvent>,
+    pending: Query<(), With<Uncover>>,
+    tiles: Query<(Entity, &Coordinates)>,
+    children: Query<&Children>,
+    tweens: Query<(), With<Tween>>,
+    detonated: Query<(), With<Detonated>>,
+) {
+    // A cascade being uncovered is part of the last action, it's undone once finished
+    if undo_evr.iter().count() == 0 || !pending.is_empty() {
+        return;
+    }
+    let first_undo = board.history.undos == 0;
+    let step = match board.history.undo() {
+        None => {
+            log::info!("Nothing to undo");
+            return;
+        }
+        Some(s) => s,
+    };
+    let options = match board_options {
+        None => BoardOptions::default(),
+        Some(o) => o.clone(),
+    };
+    let cover_size = board.tile_size - options.tile_padding;
+    let topology = board.tile_map.topology();
+    let mut flagged = Vec::new();
+    for change in step.into_iter().rev() {
+        match change {
+            Change::Uncover(coords) => {
+                let tile = match tiles.iter().find(|(_, c)| **c == coords) {
+                    Some((e, _)) => e,
+                    None => {
+                        log::error!("No tile at {} to cover again", coords);
+                        continue;
+                    }
+                };
+                // The previous cover may still be fading out, and the life spent on a bomb
+                // is given back with its flags
+                let restored = board.restore_life(coords);
+                if let Ok(tile_children) = children.get(tile) {
+                    for child in tile_children.iter() {
+                        if tweens.contains(*child) || (restored && detonated.contains(*child)) {
+                            commands.entity(*child).despawn_recursive();
+                        }
+                    }
+                }
+                let mut cover = None;
+                commands.entity(tile).with_children(|parent| {
+                    cover = Some(spawn_cover(parent, &board_assets, topology, cover_size));
+                });
+                board.cover_tile(coords, cover.expect("the cover is spawned"));
+            }
+            Change::Mark(coords) => {
+                flagged.extend(board.remove_mark(coords, None).map(|_| coords));
+            }
+            Change::Unmark { coords, index } => {
+                flagged.extend(board.add_mark(coords, Some(index)).map(|_| coords));
+            }
+        }
+    }
+    respawn_flags(&mut commands, &board, &board_assets, flagged);
+    log::info!("Undid the last action");
+    // The game goes on if its end was undone
+    timer.stopped = board.is_completed() || board.is_exploded();
+    if first_undo && board.game_over {
+        // The game was recorded without undo
+        let key = Stats::key(&board.tile_map, &GenerationMode::from_options(&options), options.lives);
+        stats.record_undo(key);
+        if let Err(e) = stats.save() {
+            log::error!("Failed to save stats: {}", e);
+        }
+    }
+}
+
+/// Applies the last undone player action again, like the player did
+#[allow(clippy::too_many_arguments)]
+pub fn redo(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    board_assets: Res<BoardAssets>,
+    mut redo_evr: EventReader<RedoEvent>,
+    pending: Query<(), With<Uncover>>,
+    mut board_completed_ewr: EventWriter<BoardCompletedEvent>,
+    mut bomb_explosion_ewr: EventWriter<BombExplosionEvent>,
+    mut tile_uncovered_ewr: EventWriter<TileUncoveredEvent>,
+    tiles: Query<(Entity, &Coordinates)>,
+) {
+    if redo_evr.iter().count() == 0 || !pending.is_empty() {
+        return;
+    }
+    let step = match board.history.redo() {
+        None => {
+            log::info!("Nothing to redo");
+            return;
+        }
+        Some(s) => s,
+    };
+    let mut flagged = Vec::new();
+    for change in step {
+        match change {
+            Change::Uncover(coords) => {
+                if let Some(cover) = board.uncover_tile(coords) {
+                    commands.entity(cover).despawn_recursive();
+                    tile_uncovered_ewr.send(TileUncoveredEvent(coords));
+                }
+                if board.tile_map.is_bomb_at(coords) {
+                    if board.try_spend_life(coords) {
+                        if let Some((tile, _)) = tiles.iter().find(|(_, c)| **c == coords) {
+                            let mines = board.tile_map.mines_at(coords) as usize;
+                            spawn_detonated_flags(&mut commands, tile, mines, Themed::Flag, &board_assets, board.tile_size);
+                        }
+                    }
+                    bomb_explosion_ewr.send(BombExplosionEvent);
+                }
+            }
+            Change::Mark(coords) => {
+                flagged.extend(board.add_mark(coords, None).map(|_| coords));
+            }
+            Change::Unmark { coords, index } => {
+                flagged.extend(board.remove_mark(coords, Some(index)).map(|_| coords));
+            }
+        }
+    }
+    respawn_flags(&mut commands, &board, &board_assets, flagged);
+    log::info!("Redid the last undone action");
+    if board.is_completed() {
+        board_completed_ewr.send(BoardCompletedEvent);
+    }
+}
+
+/// Shows the marks of the `flagged` tiles once all the changes of a step are applied
+fn respawn_flags(
+    commands: &mut Commands,
+    board: &Board,
+    board_assets: &BoardAssets,
+    mut flagged: Vec<Coordinates>,
+) {
+    flagged.sort();
+    flagged.dedup();
+    for coords in flagged {
+        if let Some(cover) = board.covered_tiles.get(&coords) {
+            set_flags(commands, *cover, board.marks_at(&coords), board_assets, board.tile_size, None);
+        }
+    }
+}
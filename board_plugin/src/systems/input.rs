@@ -13,7 +13,11 @@ pub fn input_handling(
     mut tile_mark_ewr: EventWriter<TileMarkEvent>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
 ) {
-    let window = windows.get_primary().unwrap();
+    let window = match windows.get_primary() {
+        Some(w) => w,
+        // Nothing to click on when running headless
+        None => return,
+    };
 
     for event in button_evr.iter() {
         if let ButtonState::Pressed = event.state {
@@ -1,46 +1,145 @@
-use crate::{Board, BoardAssets, TileMarkEvent};
+use crate::{Board, BoardAssets, BoardCompletedEvent, BoardTimer, Flag, Themed, TileMarkEvent, Tween, TweenKind};
+use crate::resources::{AnimationSettings, BoardOptions, GameMode, ModeRun};
 use bevy::log;
 use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use bevy::hierarchy::despawn_with_children_recursive;
+use std::time::Duration;
 
+#[allow(clippy::too_many_arguments)]
 pub fn mark_tiles(
     mut commands: Commands,
     mut board: ResMut<Board>,
     board_assets: Res<BoardAssets>,
+    settings: Res<AnimationSettings>,
+    board_options: Option<Res<BoardOptions>>,
+    mut timer: ResMut<BoardTimer>,
+    mut run: Option<ResMut<ModeRun>>,
     mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
-    query: Query<&Children>,
+    mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
 ) {
+    // Versus turns are played by `systems::versus::play_turns`
+    if board.mode.is_versus() {
+        return;
+    }
     for event in tile_mark_event_rdr.iter() {
-        if let Some((entity, mark)) = board.try_toggle_mark(&event.0) {
-            if mark {
-                commands.entity(entity).with_children(|parent| {
-                    parent
-                        // Using `.spawn()` instead of `.spawn_bundle()`
-                        .spawn(SpriteBundle {
-                            texture: board_assets.flag_material.texture.clone(),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(board.tile_size)),
-                                color: board_assets.flag_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0., 0., 1.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Flag"));
-                });
-            } else {
-                let children = match query.get(entity) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::error!("Failed to retrieve flag entity components: {}", e);
-                        continue;
-                    }
-                };
-                for child in children.iter() {
-                    commands.entity(*child).despawn_recursive();
-                }
+        board.clicks += 1;
+        if board.mode == GameMode::MineRace && board.is_wrong_mark(&event.0) {
+            // The flag is refused and the mistake costs time
+            let penalty = board_options.as_ref().map(|o| o.flag_penalty).unwrap_or_default();
+            timer.elapsed += penalty;
+            if let Some(run) = run.as_mut() {
+                run.wrong_flags += 1;
+            }
+            log::info!("No bomb left to flag on {}, {:.0}s penalty", event.0, penalty.as_secs_f32());
+            continue;
+        }
+        board.history.begin();
+        let before = board.marks_at(&event.0);
+        if let Some((entity, marks)) = board.try_toggle_mark(&event.0) {
+            let drop_in = (settings.enabled && marks > before).then_some(settings.flag_drop);
+            set_flags(&mut commands, entity, marks, &board_assets, board.tile_size, drop_in);
+        }
+        if board.mode == GameMode::MineRace && board.is_completed() {
+            log::info!("Every bomb flagged!");
+            board_completed_event_wr.send(BoardCompletedEvent);
+        }
+    }
+}
+
+/// Replaces the flags of a cover by `count` stacked ones, dropping the top one in for
+/// `drop_in` if set
+pub(crate) fn set_flags(
+    commands: &mut Commands,
+    cover: Entity,
+    count: usize,
+    board_assets: &BoardAssets,
+    tile_size: f32,
+    drop_in: Option<Duration>,
+) {
+    set_themed_flags(commands, cover, count, Themed::Flag, board_assets, tile_size, drop_in);
+}
+
+/// `set_flags` with the flags styled as `themed`, e.g. the flags of a versus player
+pub(crate) fn set_themed_flags(
+    commands: &mut Commands,
+    cover: Entity,
+    count: usize,
+    themed: Themed,
+    board_assets: &BoardAssets,
+    tile_size: f32,
+    drop_in: Option<Duration>,
+) {
+    // The cover's other children, like the probability overlay, are kept
+    commands.add(DespawnFlags(cover));
+    if count == 0 {
+        return;
+    }
+    commands.entity(cover).with_children(|parent| {
+        for index in 0..count {
+            let drop_in = drop_in.filter(|_| index + 1 == count);
+            spawn_flag(parent, board_assets, themed, stacked(index, count, tile_size), drop_in);
+        }
+    });
+}
+
+/// Despawns the `Flag` children of an entity
+struct DespawnFlags(Entity);
+
+impl Command for DespawnFlags {
+    fn write(self, world: &mut World) {
+        let children: Vec<Entity> = match world.get::<Children>(self.0) {
+            Some(c) => c.iter().copied().collect(),
+            None => return,
+        };
+        for child in children {
+            if world.get::<Flag>(child).is_some() {
+                despawn_with_children_recursive(world, child);
             }
         }
     }
 }
 
+/// Position and size of the `index`th of `count` sprites piled on a tile, e.g. the flags
+/// or bombs of a multi-mine tile
+pub(crate) fn stacked(index: usize, count: usize, tile_size: f32) -> (Vec3, f32) {
+    if count <= 1 {
+        return (Vec3::new(0., 0., 1.), tile_size);
+    }
+    let offset = (index as f32 - (count - 1) as f32 / 2.) * tile_size / 6.;
+    (Vec3::new(offset, offset, 1. + index as f32 / 100.), tile_size * 0.7)
+}
 
+/// Spawns a flag of a marked cover at `rest` with a `size`, dropping it in for `drop_in` if set
+fn spawn_flag(
+    parent: &mut ChildBuilder,
+    board_assets: &BoardAssets,
+    themed: Themed,
+    (rest, size): (Vec3, f32),
+    drop_in: Option<Duration>,
+) {
+    let color = match themed {
+        Themed::PlayerFlag(player) => board_assets.player_flag_color(player),
+        _ => board_assets.flag_material.color,
+    };
+    let mut flag = parent
+        // Using `.spawn()` instead of `.spawn_bundle()`
+        .spawn(SpriteBundle {
+            texture: board_assets.flag_material.texture.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                color,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(rest),
+            ..Default::default()
+        });
+    flag.insert(Name::new("Flag")).insert(Flag).insert(themed);
+    if let Some(duration) = drop_in {
+        let drop_in = TweenKind::DropIn {
+            rest,
+            height: size,
+        };
+        flag.insert(Tween::new(drop_in, duration));
+    }
+}
@@ -1,3 +1,13 @@
+pub mod animation;
+pub mod hint;
+pub mod history;
 pub mod input;
 pub mod mark;
+pub mod mode;
+pub mod overlay;
+pub mod pause;
+pub mod sound;
+pub mod summary;
+pub mod theme;
 pub mod uncover;
+pub mod versus;
@@ -0,0 +1,110 @@
+use std::time::Duration;
+use bevy::ecs::schedule::StateData;
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, BoardTimer, GameOverEvent, ModeOverEvent};
+use crate::resources::{BoardOptions, GameMode, GenerationMode, ModeRun, Stats};
+
+/// Starts a time attack or mine race run, unless the board continues a time attack
+pub fn start_run(
+    mut commands: Commands,
+    board_options: Option<Res<BoardOptions>>,
+    run: Option<ResMut<ModeRun>>,
+) {
+    let mode = board_options.map(|o| o.mode).unwrap_or_default();
+    if mode == GameMode::Classic || mode.is_versus() {
+        commands.remove_resource::<ModeRun>();
+        return;
+    }
+    if let Some(mut run) = run {
+        if run.continuing && run.mode == mode {
+            run.continuing = false;
+            return;
+        }
+    }
+    commands.insert_resource(ModeRun::new(mode));
+}
+
+/// Ends a time attack once its time limit is reached
+pub fn tick_time_attack(
+    mut board: ResMut<Board>,
+    mut timer: ResMut<BoardTimer>,
+    board_options: Option<Res<BoardOptions>>,
+    run: Option<ResMut<ModeRun>>,
+    mut stats: ResMut<Stats>,
+    mut mode_over_ewr: EventWriter<ModeOverEvent>,
+) {
+    let mut run = match run {
+        Some(r) if r.mode == GameMode::TimeAttack && !r.over => r,
+        _ => return,
+    };
+    let options = board_options.map(|o| o.clone()).unwrap_or_default();
+    if run.time_left(options.time_limit, timer.elapsed) > Duration::ZERO {
+        return;
+    }
+    log::info!("Time's up!");
+    timer.stopped = true;
+    // The unfinished board isn't summarized
+    board.game_over = true;
+    run.score = Some(run.boards_cleared as f32);
+    finish(&mut run, &board, &options, &mut stats, &mut mode_over_ewr);
+}
+
+/// Moves a time attack on to the next board or ends the run with the game
+pub fn advance_run<T: StateData>(
+    board: Res<Board>,
+    board_options: Option<Res<BoardOptions>>,
+    run: Option<ResMut<ModeRun>>,
+    mut stats: ResMut<Stats>,
+    mut state: ResMut<State<T>>,
+    mut game_over_evr: EventReader<GameOverEvent>,
+    mut mode_over_ewr: EventWriter<ModeOverEvent>,
+) {
+    let mut run = match run {
+        Some(r) if !r.over => r,
+        _ => return,
+    };
+    let options = board_options.map(|o| o.clone()).unwrap_or_default();
+    for event in game_over_evr.iter() {
+        let summary = &event.0;
+        run.assisted |= summary.is_assisted();
+        match run.mode {
+            GameMode::TimeAttack if summary.won => {
+                run.boards_cleared += 1;
+                run.previous_boards += summary.elapsed;
+                run.continuing = true;
+                log::info!("{} boards cleared, next board", run.boards_cleared);
+                state.overwrite_restart();
+            }
+            GameMode::TimeAttack => {
+                run.score = Some(run.boards_cleared as f32);
+                finish(&mut run, &board, &options, &mut stats, &mut mode_over_ewr);
+            }
+            GameMode::MineRace => {
+                run.score = summary.won.then_some(summary.elapsed.as_secs_f32());
+                finish(&mut run, &board, &options, &mut stats, &mut mode_over_ewr);
+            }
+            GameMode::Classic | GameMode::Versus | GameMode::Flags => (),
+        }
+    }
+}
+
+/// Records the finished run in the stats and saves them
+fn finish(
+    run: &mut ModeRun,
+    board: &Board,
+    options: &BoardOptions,
+    stats: &mut Stats,
+    mode_over_ewr: &mut EventWriter<ModeOverEvent>,
+) {
+    run.over = true;
+    let board_key = Stats::key(&board.tile_map, &GenerationMode::from_options(options), options.lives);
+    let key = Stats::mode_key(run.mode, options.time_limit, &board_key);
+    if stats.record_mode(key, run) {
+        log::info!("New best {} score: {:?}", run.mode, run.score);
+    }
+    if let Err(e) = stats.save() {
+        log::error!("Failed to save stats: {}", e);
+    }
+    mode_over_ewr.send(ModeOverEvent(run.clone()));
+}
@@ -0,0 +1,95 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, BoardAssets, Probability, ProbabilityOverlay};
+use crate::resources::Typing;
+use crate::solver::{probabilities, supports, BoardView};
+
+/// Color of a tile which is certainly a mine, scaled by the probability
+const MINE_COLOR: Color = Color::rgba(1., 0., 0., 0.6);
+
+pub fn toggle_probability_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut board: ResMut<Board>,
+    mut overlay: ResMut<ProbabilityOverlay>,
+    typing: Option<Res<Typing>>,
+) {
+    if typing.is_some() {
+        return;
+    }
+    if keys.just_pressed(overlay.toggle_key) {
+        overlay.enabled = !overlay.enabled;
+        log::info!("Probability overlay enabled: {}", overlay.enabled);
+        // Any use of the overlay makes the game assisted
+        if overlay.enabled {
+            board.overlay_used = true;
+        }
+    }
+}
+
+/// Recomputes the overlay whenever the board or the overlay settings change
+pub fn update_probability_overlay(
+    mut commands: Commands,
+    board: Res<Board>,
+    board_assets: Res<BoardAssets>,
+    overlay: Res<ProbabilityOverlay>,
+    shown: Query<Entity, With<Probability>>,
+) {
+    if !board.is_changed() && !overlay.is_changed() {
+        return;
+    }
+    for entity in shown.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !overlay.enabled {
+        return;
+    }
+    if !supports(&board.tile_map) {
+        log::warn!("No probability overlay on multi-mine boards");
+        return;
+    }
+
+    let probabilities = probabilities(&BoardView::from_board(&board));
+    // Estimates are shown with a `~` prefix so they aren't taken for exact values
+    let approximate = !probabilities.is_exact();
+    if approximate {
+        log::warn!("Probability overlay is approximate, the board is too open to solve exactly");
+    }
+    let prefix = if approximate { "~" } else { "" };
+    for (coords, value) in probabilities.iter() {
+        let cover = match board.covered_tiles.get(&coords) {
+            Some(e) => *e,
+            None => continue,
+        };
+        let mut color = MINE_COLOR;
+        color.set_a(MINE_COLOR.a() * value);
+        commands.entity(cover).with_children(|parent| {
+            parent
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(board.tile_size)),
+                        color,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., 0., 1.),
+                    ..Default::default()
+                })
+                .insert(Name::new("Probability"))
+                .insert(Probability { value, approximate })
+                .with_children(|parent| {
+                    parent.spawn(Text2dBundle {
+                        text: Text::from_section(
+                            format!("{}{:.0}%", prefix, value * 100.),
+                            TextStyle {
+                                color: Color::WHITE,
+                                font: board_assets.bomb_counter_font.clone(),
+                                font_size: board.tile_size / 3.,
+                            },
+                        )
+                        .with_alignment(TextAlignment::CENTER),
+                        transform: Transform::from_xyz(0., 0., 1.),
+                        ..Default::default()
+                    });
+                });
+        });
+    }
+}
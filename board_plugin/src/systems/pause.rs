@@ -0,0 +1,16 @@
+use bevy::prelude::*;
+use crate::Board;
+
+/// Hides the board while the game is paused so it can't be studied off the clock.
+/// The timer and input systems only run while the game is the active state
+pub fn hide_board(board: Res<Board>, mut visibilities: Query<&mut Visibility>) {
+    if let Ok(mut visibility) = visibilities.get_mut(board.entity) {
+        visibility.is_visible = false;
+    }
+}
+
+pub fn show_board(board: Res<Board>, mut visibilities: Query<&mut Visibility>) {
+    if let Ok(mut visibility) = visibilities.get_mut(board.entity) {
+        visibility.is_visible = true;
+    }
+}
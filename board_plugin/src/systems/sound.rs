@@ -0,0 +1,64 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crate::{Board, BoardCompletedEvent, BombExplosionEvent, TileMarkEvent};
+use crate::events::{SoundCueEvent, TileUncoveredEvent};
+use crate::resources::{SoundCue, SoundSettings, Tile};
+
+/// Maps the board events of the frame to rate limited `SoundCueEvent`s
+#[allow(clippy::too_many_arguments)]
+pub fn emit_sound_cues(
+    board: Res<Board>,
+    settings: Res<SoundSettings>,
+    time: Res<Time>,
+    // Time each cue was last sent
+    mut last_sent: Local<HashMap<SoundCue, Duration>>,
+    mut tile_uncovered_evr: EventReader<TileUncoveredEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut sound_cue_ewr: EventWriter<SoundCueEvent>,
+) {
+    let mut cues = Vec::new();
+
+    // A single cue for all the tiles uncovered this frame
+    let uncovered = tile_uncovered_evr
+        .iter()
+        .map(|e| board.tile_map[e.0.y as usize][e.0.x as usize])
+        .filter(|tile| !tile.is_bomb())
+        .fold(None, |cue, tile| match (cue, tile) {
+            (_, Tile::Empty) | (Some(SoundCue::Cascade), _) => Some(SoundCue::Cascade),
+            _ => Some(SoundCue::Reveal),
+        });
+    cues.extend(uncovered);
+    for TileMarkEvent(coords) in tile_mark_evr.iter() {
+        if board.marked_tiles.contains(coords) {
+            cues.push(SoundCue::Flag);
+        } else if board.covered_tiles.contains_key(coords) {
+            cues.push(SoundCue::Unflag);
+        }
+    }
+    if bomb_explosion_evr.iter().count() > 0 {
+        cues.push(SoundCue::Explosion);
+    }
+    if board_completed_evr.iter().count() > 0 {
+        cues.push(SoundCue::Win);
+    }
+
+    if settings.muted {
+        return;
+    }
+    let now = time.elapsed();
+    for cue in cues {
+        if let Some(last) = last_sent.get(&cue) {
+            if now.saturating_sub(*last) < settings.min_interval {
+                continue;
+            }
+        }
+        last_sent.insert(cue, now);
+        sound_cue_ewr.send(SoundCueEvent {
+            cue,
+            volume: settings.volume,
+        });
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, BoardCompletedEvent, BombExplosionEvent, BoardTimer, GameOverEvent};
+use crate::solver::difficulty;
+use crate::resources::{BoardOptions, DailyHistory, DailyResult, GameMode, GenerationMode, GameSummary, Stats};
+
+pub fn tick_timer(time: Res<Time>, mut timer: ResMut<BoardTimer>) {
+    if !timer.stopped {
+        timer.elapsed += time.delta();
+    }
+}
+
+/// Stops the timer and sends the summary on the first completion or explosion, with the
+/// difficulty of the board
+pub fn summarize_game(
+    mut board: ResMut<Board>,
+    board_options: Option<Res<BoardOptions>>,
+    mut timer: ResMut<BoardTimer>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut game_over_ewr: EventWriter<GameOverEvent>,
+) {
+    let won = board_completed_evr.iter().count() > 0;
+    // Explosions paid for with a life don't end the game
+    let lost = bomb_explosion_evr.iter().count() > 0 && board.is_exploded();
+    if !(won || lost) {
+        return;
+    }
+    timer.stopped = true;
+    // Undoing the end of a game lets it go on, but it's only summarized once
+    if board.game_over {
+        return;
+    }
+    board.game_over = true;
+    let safe_start = board_options.map(|o| o.safe_start).unwrap_or_default();
+    board.metrics.difficulty = Some(difficulty(&board.tile_map, safe_start));
+    let summary = GameSummary {
+        won: won && !lost,
+        elapsed: timer.elapsed,
+        clicks: board.clicks,
+        hints_used: board.hints_used,
+        overlay_used: board.overlay_used,
+        undos: board.history.undos,
+        metrics: board.metrics,
+        seed: board.seed,
+    };
+    log::info!("{}", summary);
+    game_over_ewr.send(GameOverEvent(summary));
+}
+
+/// Records the finished game in the stats and saves them
+pub fn record_stats(
+    board: Res<Board>,
+    board_options: Option<Res<BoardOptions>>,
+    mut stats: ResMut<Stats>,
+    mut game_over_evr: EventReader<GameOverEvent>,
+) {
+    let options = match board_options {
+        None => BoardOptions::default(),
+        Some(o) => o.clone(),
+    };
+    // Time attacks and mine races are recorded by run, see `systems::mode`
+    if options.mode != GameMode::Classic {
+        return;
+    }
+    for event in game_over_evr.iter() {
+        let key = Stats::key(&board.tile_map, &GenerationMode::from_options(&options), options.lives);
+        if stats.record(key, &event.0) {
+            log::info!("New best time: {:.2}s", event.0.elapsed.as_secs_f32());
+        }
+        if let Err(e) = stats.save() {
+            log::error!("Failed to save stats: {}", e);
+        }
+    }
+}
+
+/// Records the first game of a daily challenge in the daily history and saves it
+pub fn record_daily(
+    board_options: Option<Res<BoardOptions>>,
+    mut history: ResMut<DailyHistory>,
+    mut game_over_evr: EventReader<GameOverEvent>,
+) {
+    let date = match board_options.and_then(|o| o.daily) {
+        None => return,
+        Some(d) => d,
+    };
+    for event in game_over_evr.iter() {
+        // Replays of the day don't count
+        if !history.record(DailyResult::from_summary(date, &event.0)) {
+            continue;
+        }
+        if let Some(text) = history.share_text(date) {
+            log::info!("{}", text);
+        }
+        if let Err(e) = history.save() {
+            log::error!("Failed to save the daily history: {}", e);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use crate::{Board, BoardAssets, BombNeighbor, Themed};
+use crate::resources::{NumberCue, NumberStyle};
+
+/// Restyles the spawned board when the `BoardAssets` or `NumberStyle` change,
+/// e.g. on a theme switch
+#[allow(clippy::too_many_arguments)]
+pub fn apply_board_assets(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    number_style: Res<NumberStyle>,
+    mut sprites: Query<(&Themed, &mut Sprite, &mut Handle<Image>)>,
+    mut texts: Query<(Entity, &Themed, &Parent, &mut Text)>,
+    neighbors: Query<&BombNeighbor>,
+    themed: Query<(Entity, &Themed)>,
+) {
+    if !board_assets.is_changed() && !number_style.is_changed() {
+        return;
+    }
+    let topology = board.map(|b| b.tile_map.topology()).unwrap_or_default();
+    for (themed, mut sprite, mut texture) in sprites.iter_mut() {
+        if let Themed::PlayerFlag(player) = themed {
+            sprite.color = board_assets.player_flag_color(*player);
+            *texture = board_assets.flag_material.texture.clone();
+            continue;
+        }
+        let (material, tile) = match themed {
+            Themed::Background => (&board_assets.board_material, false),
+            Themed::Tile => (&board_assets.tile_material, true),
+            Themed::Cover => (&board_assets.covered_tile_material, true),
+            Themed::Flag => (&board_assets.flag_material, false),
+            Themed::Bomb => (&board_assets.bomb_material, false),
+            Themed::PlayerFlag(_) | Themed::Counter | Themed::Cue => continue,
+        };
+        sprite.color = material.color;
+        *texture = if tile {
+            board_assets.tile_texture(material, topology)
+        } else {
+            material.texture.clone()
+        };
+    }
+
+    // Cues are rebuilt from scratch as they depend on the style, font and colors
+    for (entity, themed) in themed.iter() {
+        if *themed == Themed::Cue {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (entity, themed, parent, mut text) in texts.iter_mut() {
+        if *themed != Themed::Counter {
+            continue;
+        }
+        let count = match neighbors.get(parent.get()) {
+            Ok(n) => n.count,
+            Err(_) => continue,
+        };
+        let size = text.sections[0].style.font_size;
+        for section in text.sections.iter_mut() {
+            section.style.color = number_style.color(&board_assets, count);
+            section.style.font = board_assets.bomb_counter_font.clone();
+        }
+        commands.entity(entity).with_children(|parent| {
+            spawn_count_cue(parent, count, &number_style, &board_assets, size);
+        });
+    }
+}
+
+/// Spawns the secondary cue of a bomb counter as children of its text
+pub(crate) fn spawn_count_cue(
+    parent: &mut ChildBuilder,
+    count: u8,
+    number_style: &NumberStyle,
+    board_assets: &BoardAssets,
+    size: f32,
+) {
+    match number_style.cue {
+        NumberCue::None => (),
+        NumberCue::Outline => {
+            let offset = (size / 24.).max(1.);
+            for (dx, dy) in [(-1., 0.), (1., 0.), (0., -1.), (0., 1.)] {
+                parent
+                    .spawn(Text2dBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: count.to_string(),
+                                style: TextStyle {
+                                    color: Color::BLACK,
+                                    font: board_assets.bomb_counter_font.clone(),
+                                    font_size: size,
+                                },
+                            }],
+                            alignment: TextAlignment {
+                                vertical: VerticalAlign::Center,
+                                horizontal: HorizontalAlign::Center,
+                            },
+                        },
+                        // Behind the digit
+                        transform: Transform::from_xyz(dx * offset, dy * offset, -0.5),
+                        ..Default::default()
+                    })
+                    .insert(Themed::Cue);
+            }
+        }
+        NumberCue::Pips => {
+            // Rows of up to 8 pips, stacked up from the bottom of the tile
+            let spacing = size / 10.;
+            for i in 0..count {
+                let row_length = (count - i / 8 * 8).min(8);
+                let start = -(row_length as f32 - 1.) * spacing / 2.;
+                parent
+                    .spawn(SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(size / 14.)),
+                            color: number_style.color(board_assets, count),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            start + (i % 8) as f32 * spacing,
+                            -size * 0.4 + (i / 8) as f32 * spacing,
+                            0.,
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(Themed::Cue);
+            }
+        }
+    }
+}
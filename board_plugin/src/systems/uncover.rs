@@ -1,45 +1,83 @@
 use bevy::log;
 use bevy::prelude::*;
-use crate::{Board, Bomb, BombNeighbor, Coordinates, Uncover};
+use crate::{Board, BoardAssets, Coordinates, Detonated, Themed, Tween, TweenKind, Uncover};
 use crate::{BoardCompletedEvent, BombExplosionEvent};
-use crate::events::TileTriggerEvent;
+use crate::events::{TileTriggerEvent, TileUncoveredEvent};
+use crate::resources::{AnimationSettings, RevealStyle, Topology};
+use crate::systems::mark::set_themed_flags;
 
 pub fn trigger_event_handler(
     mut commands: Commands,
-    board: Res<Board>,
+    mut board: ResMut<Board>,
     mut tile_trigger_evr: EventReader<TileTriggerEvent>,
 ) {
+    // Versus turns are played by `systems::versus::play_turns`
+    if board.mode.is_versus() {
+        return;
+    }
+    // The triggers of a frame, e.g. a chord, are undone together
+    if !tile_trigger_evr.is_empty() {
+        board.history.begin();
+    }
     for trigger_event in tile_trigger_evr.iter() {
+        board.clicks += 1;
         if let Some(entity) = board.tile_to_uncover(&trigger_event.0) {
             commands.entity(*entity).insert(Uncover);
         }
+        for entity in board.chord_tiles(&trigger_event.0) {
+            commands.entity(entity).insert(Uncover);
+        }
     }
 }
 
+/// Reveals the tiles whose cover was given an `Uncover` with `Board::reveal`, the
+/// cascade goes on with a ring of tiles per frame
+#[allow(clippy::too_many_arguments)]
 pub fn uncover_tiles(
     mut commands: Commands,
     mut board: ResMut<Board>,
+    board_assets: Res<BoardAssets>,
+    settings: Res<AnimationSettings>,
+    // Rings of the current cascade uncovered so far, one per frame
+    mut ring: Local<u32>,
     children: Query<(Entity, &Parent), With<Uncover>>,
-    parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>,
+    tiles: Query<&Coordinates>,
     mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
     mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    mut tile_uncovered_event_wr: EventWriter<TileUncoveredEvent>,
 ) {
+    if children.is_empty() {
+        *ring = 0;
+        return;
+    }
     for (entity, parent) in children.iter() {
-        commands
-            .entity(entity)
-            .despawn_recursive();
-
-        let (coords, bomb, bomb_counter) = match parents.get(parent.get()) {
-            Ok(v) => v,
+        remove_cover(&mut commands, &board_assets, &settings, entity, *ring);
+        let coords = match tiles.get(parent.get()) {
+            Ok(c) => *c,
             Err(e) => {
                 log::error!("{}", e);
                 continue;
             }
         };
+        let cascade = match board.reveal(coords) {
+            None => {
+                log::debug!("Tried to uncover an already uncovered tile");
+                continue;
+            }
+            Some(c) => c,
+        };
+        log::debug!("Uncovered tile {} (entity: {:?})", coords, entity);
+        tile_uncovered_event_wr.send(TileUncoveredEvent(coords));
 
-        match board.try_uncover_tile(coords) {
-            None => log::debug!("Tried to uncover an already uncovered tile"),
-            Some(e) => log::debug!("Uncovered tile {} (entity: {:?})", coords, e),
+        if board.tile_map.is_bomb_at(coords) {
+            if board.detonated.contains(&coords) {
+                log::info!("Boom ! {} lives left", board.lives.unwrap_or_default());
+                let mines = board.tile_map.mines_at(coords) as usize;
+                spawn_detonated_flags(&mut commands, parent.get(), mines, Themed::Flag, &board_assets, board.tile_size);
+            } else {
+                log::info!("Boom !");
+            }
+            bomb_explosion_event_wr.send(BombExplosionEvent);
         }
 
         if board.is_completed() {
@@ -47,17 +85,84 @@ pub fn uncover_tiles(
             board_completed_event_wr.send(BoardCompletedEvent);
         }
 
-        if bomb.is_some() {
-            log::info!("Boom !");
-            bomb_explosion_event_wr.send(BombExplosionEvent);
-        } else if bomb_counter.is_none() {
-            // If the tile is empty we propagate the uncovering by
-            // adding the 'Uncover' component to adjacent tiles which
-            // will then be removed next frame.
-            for entity in board.adjacent_covered_tiles(*coords) {
-                commands.entity(entity).insert(Uncover);
+        // The next ring is uncovered next frame
+        for tile in cascade {
+            if let Some(cover) = board.covered_tiles.get(&tile) {
+                commands.entity(*cover).insert(Uncover);
             }
         }
     }
+    *ring += 1;
+}
+
+/// Removes an uncovered tile's cover, fading it out after a delay growing with its `ring`
+/// in the cascade when animations are enabled
+fn remove_cover(
+    commands: &mut Commands,
+    board_assets: &BoardAssets,
+    settings: &AnimationSettings,
+    cover: Entity,
+    ring: u32,
+) {
+    if !settings.enabled {
+        commands.entity(cover).despawn_recursive();
+        return;
+    }
+    // The tile is uncovered right away, only its cover lingers while it animates
+    let kind = match settings.reveal_style {
+        RevealStyle::Fade => TweenKind::FadeOut {
+            alpha: board_assets.covered_tile_material.color.a(),
+        },
+        RevealStyle::Flip => TweenKind::Flip,
+    };
+    commands
+        .entity(cover)
+        .remove::<Uncover>()
+        .remove::<Themed>()
+        .insert(Name::new("Revealed cover"))
+        .insert(Tween::new(kind, settings.reveal).with_delay(settings.ripple_delay * ring));
+}
+
+/// Flags the bomb of `tile` once it's uncovered at the cost of a life, or claimed in a
+/// versus game with the flags styled as `themed`
+pub(crate) fn spawn_detonated_flags(
+    commands: &mut Commands,
+    tile: Entity,
+    mines: usize,
+    themed: Themed,
+    board_assets: &BoardAssets,
+    tile_size: f32,
+) {
+    // Above the bomb, where the cover was
+    let flags = commands
+        .spawn(SpatialBundle::from_transform(Transform::from_xyz(0., 0., 2.)))
+        .insert(Name::new("Detonated flags"))
+        .insert(Detonated)
+        .id();
+    commands.entity(tile).add_child(flags);
+    set_themed_flags(commands, flags, mines, themed, board_assets, tile_size, None);
+}
 
+/// Spawns the cover of a tile, `size` excluding the padding
+pub(crate) fn spawn_cover(
+    parent: &mut ChildBuilder,
+    board_assets: &BoardAssets,
+    topology: Topology,
+    size: f32,
+) -> Entity {
+    let material = &board_assets.covered_tile_material;
+    parent
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(topology.tile_sprite_size(size)),
+                color: material.color,
+                ..Default::default()
+            },
+            texture: board_assets.tile_texture(material, topology),
+            transform: Transform::from_xyz(0., 0., 2.),
+            ..Default::default()
+        })
+        .insert(Name::new("Tile cover"))
+        .insert(Themed::Cover)
+        .id()
 }
@@ -0,0 +1,121 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::{Board, BoardAssets, BoardTimer, Coordinates, Themed, Uncover};
+use crate::events::{TileMarkEvent, TileTriggerEvent, TileUncoveredEvent, VersusOverEvent};
+use crate::resources::{BoardOptions, GameMode, Player, VersusGame};
+use crate::systems::uncover::spawn_detonated_flags;
+
+/// Starts a versus game with the first player, or drops the previous one
+pub fn start_versus(mut commands: Commands, board_options: Option<Res<BoardOptions>>) {
+    let mode = board_options.map(|o| o.mode).unwrap_or_default();
+    if mode.is_versus() {
+        commands.insert_resource(VersusGame::default());
+    } else {
+        commands.remove_resource::<VersusGame>();
+    }
+}
+
+/// Plays the triggers and marks of the player whose turn it is, scoring the bombs they
+/// claim, and ends the game once it's decided. The turns aren't recorded in the history,
+/// so they can't be undone
+#[allow(clippy::too_many_arguments)]
+pub fn play_turns(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    board_assets: Res<BoardAssets>,
+    mut timer: ResMut<BoardTimer>,
+    game: Option<ResMut<VersusGame>>,
+    parents: Query<&Parent>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    mut tile_uncovered_ewr: EventWriter<TileUncoveredEvent>,
+    mut versus_over_ewr: EventWriter<VersusOverEvent>,
+) {
+    let mut game = match game {
+        Some(g) if !g.over => g,
+        _ => return,
+    };
+    let mode = board.mode;
+    for event in tile_trigger_evr.iter() {
+        let coords = event.0;
+        board.clicks += 1;
+        if board.tile_map.is_bomb_at(coords) && board.covered_tiles.contains_key(&coords) {
+            // The bomb scores for the player in a flags game, for the opponent otherwise
+            let player = match mode {
+                GameMode::Flags => game.turn,
+                _ => game.turn.next(),
+            };
+            claim(&mut commands, &mut board, &board_assets, &parents, coords, player, &mut game);
+            tile_uncovered_ewr.send(TileUncoveredEvent(coords));
+            if mode != GameMode::Flags {
+                game.pass_turn();
+            }
+            continue;
+        }
+        let mut covers: Vec<Entity> = board.tile_to_uncover(&coords).copied().into_iter().collect();
+        covers.extend(board.chord_tiles(&coords));
+        if covers.is_empty() {
+            continue;
+        }
+        for cover in covers {
+            commands.entity(cover).insert(Uncover);
+        }
+        if mode == GameMode::Flags {
+            game.pass_turn();
+        }
+    }
+    for event in tile_mark_evr.iter() {
+        let coords = event.0;
+        // Bombs of a flags game are claimed by revealing them
+        if mode != GameMode::Versus || !board.covered_tiles.contains_key(&coords) {
+            continue;
+        }
+        board.clicks += 1;
+        if board.tile_map.is_bomb_at(coords) {
+            let player = game.turn;
+            claim(&mut commands, &mut board, &board_assets, &parents, coords, player, &mut game);
+        } else {
+            log::info!("{} flagged {}, there's no bomb", game.turn, coords);
+        }
+        game.pass_turn();
+    }
+    if !game.is_decided(board.tile_map.bomb_count()) {
+        return;
+    }
+    game.over = true;
+    board.game_over = true;
+    timer.stopped = true;
+    let winner = game.leader();
+    match winner {
+        Some(player) => log::info!("{} wins {} to {}", player, game.score(player), game.score(player.next())),
+        None => log::info!("Draw, {} all", game.score(Player::One)),
+    }
+    versus_over_ewr.send(VersusOverEvent {
+        scores: game.scores,
+        winner,
+    });
+}
+
+/// Uncovers the bomb at `coords` and flags it in the colors of `player`, who scores it
+fn claim(
+    commands: &mut Commands,
+    board: &mut Board,
+    board_assets: &BoardAssets,
+    parents: &Query<&Parent>,
+    coords: Coordinates,
+    player: Player,
+    game: &mut VersusGame,
+) {
+    let cover = match board.claim_mine(coords) {
+        None => return,
+        Some(c) => c,
+    };
+    let mines = board.tile_map.mines_at(coords);
+    game.claim(player, mines.into());
+    log::info!("{} claims {} on {}", player, mines, coords);
+    if let Ok(tile) = parents.get(cover) {
+        let themed = Themed::PlayerFlag(player);
+        spawn_detonated_flags(commands, tile.get(), mines.into(), themed, board_assets, board.tile_size);
+    }
+    commands.entity(cover).despawn_recursive();
+}
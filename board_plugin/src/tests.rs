@@ -0,0 +1,1002 @@
+//! Headless integration tests driving `BoardPlugin` through events
+
+use bevy::ecs::system::CommandQueue;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::window::WindowPlugin;
+use std::time::Duration;
+use crate::components::{Bomb, Coordinates, Probability, Themed, Tween, Uncover, WorldCoordinates};
+use crate::events::*;
+use crate::resources::tile::Tile;
+use crate::date::Date;
+use crate::solver::HintKind;
+use crate::systems::mark::set_flags;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{
+    AnimationSettings, Board, BoardAssets, BoardOptions, BoardTimer, ColorPalette, DailyHistory,
+    EndlessBoard, EndlessOptions, GameMode, HighScores, ModeRun, NeighborhoodRule, NumberCue,
+    NumberStyle, Player, SoundCue, SoundSettings, SpriteMaterial, Stats, TileSize, Topology, Typing,
+    VersusGame,
+};
+use crate::{BoardPlugin, EndlessPlugin};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum TestState {
+    InGame,
+    Paused,
+}
+
+/// Events of type `E` received since the app started
+#[derive(Resource)]
+struct Recorded<E>(Vec<E>);
+
+fn record<E: Clone + Send + Sync + 'static>(
+    mut recorded: ResMut<Recorded<E>>,
+    mut events: EventReader<E>,
+) {
+    recorded.0.extend(events.iter().cloned());
+}
+
+/// App running `BoardPlugin` without a window or GPU
+struct Harness {
+    app: App,
+}
+
+impl Harness {
+    /// Maximum number of frames `settle` waits for cascades to finish
+    const MAX_FRAMES: usize = 100;
+
+    /// Builds the app and creates the board from `tile_map`
+    fn new(tile_map: TileMap) -> Self {
+        Self::with_options(tile_map, BoardOptions::default())
+    }
+
+    /// Builds the app and creates the board from `tile_map` with `options`, e.g. for lives
+    /// or a game mode. The map size, bomb count and tile size are the harness' own
+    fn with_options(tile_map: TileMap, options: BoardOptions) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(InputPlugin)
+            .add_state(TestState::InGame)
+            .insert_resource(BoardOptions {
+                map_size: (tile_map.width(), tile_map.height()),
+                bomb_count: tile_map.bomb_count(),
+                tile_size: TileSize::Fixed(10.),
+                ..options
+            })
+            .insert_resource(Self::board_assets())
+            .insert_resource(tile_map)
+            .add_plugin(BoardPlugin {
+                running_state: TestState::InGame,
+            });
+        Self::record::<BombExplosionEvent>(&mut app);
+        Self::record::<BoardCompletedEvent>(&mut app);
+        Self::record::<HintEvent>(&mut app);
+        Self::record::<GameOverEvent>(&mut app);
+        Self::record::<SoundCueEvent>(&mut app);
+        Self::record::<ModeOverEvent>(&mut app);
+        Self::record::<VersusOverEvent>(&mut app);
+
+        let mut harness = Self { app };
+        harness.app.update();
+        harness
+    }
+
+    fn board_assets() -> BoardAssets {
+        BoardAssets {
+            label: "Test".to_string(),
+            board_material: SpriteMaterial::default(),
+            tile_material: SpriteMaterial::default(),
+            covered_tile_material: SpriteMaterial::default(),
+            bomb_counter_font: Default::default(),
+            bomb_counter_colors: BoardAssets::default_colors(),
+            flag_material: SpriteMaterial::default(),
+            player_flag_colors: BoardAssets::default_player_colors(),
+            bomb_material: SpriteMaterial::default(),
+            hex_texture: Default::default(),
+            sounds: Default::default(),
+        }
+    }
+
+    fn record<E: Clone + Send + Sync + 'static>(app: &mut App) {
+        app.insert_resource(Recorded::<E>(Vec::new()))
+            .add_system_to_stage(CoreStage::Last, record::<E>);
+    }
+
+    /// Sends a `TileTriggerEvent` and waits for the uncovering to finish
+    fn trigger(&mut self, x: u16, y: u16) {
+        self.app.world.send_event(TileTriggerEvent(Coordinates { x, y }));
+        self.settle();
+    }
+
+    /// Sends a `TileMarkEvent` and runs a frame
+    fn mark(&mut self, x: u16, y: u16) {
+        self.app.world.send_event(TileMarkEvent(Coordinates { x, y }));
+        self.app.update();
+    }
+
+    /// Sends an `UndoEvent` and runs a frame
+    fn undo(&mut self) {
+        self.app.world.send_event(UndoEvent);
+        self.app.update();
+    }
+
+    /// Sends a `RedoEvent` and runs a frame
+    fn redo(&mut self) {
+        self.app.world.send_event(RedoEvent);
+        self.app.update();
+    }
+
+    /// Coordinates of the covered tiles, sorted
+    fn covered(&self) -> Vec<Coordinates> {
+        let mut covered: Vec<Coordinates> = self.board().covered_tiles.keys().copied().collect();
+        covered.sort();
+        covered
+    }
+
+    /// Presses and releases `key`, running a frame for each
+    fn press(&mut self, key: KeyCode) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key),
+                state,
+            });
+            self.app.update();
+        }
+    }
+
+    /// Runs frames until no tile is left to uncover
+    fn settle(&mut self) {
+        for _ in 0..Self::MAX_FRAMES {
+            self.app.update();
+            let mut pending = self.app.world.query_filtered::<(), With<Uncover>>();
+            if pending.iter(&self.app.world).next().is_none() {
+                return;
+            }
+        }
+        panic!("Uncovering did not settle after {} frames", Self::MAX_FRAMES);
+    }
+
+    fn board(&self) -> &Board {
+        self.app.world.resource::<Board>()
+    }
+
+    fn recorded<E: Send + Sync + 'static>(&self) -> &[E] {
+        &self.app.world.resource::<Recorded<E>>().0
+    }
+
+    /// Runs frames for at least `duration` of real time, letting animations finish
+    fn wait(&mut self, duration: Duration) {
+        let start = std::time::Instant::now();
+        while start.elapsed() < duration {
+            std::thread::sleep(Duration::from_millis(5));
+            self.app.update();
+        }
+        self.app.update();
+    }
+
+    /// Number of secondary cues spawned on bomb counters
+    fn cues(&mut self) -> usize {
+        let mut query = self.app.world.query::<&Themed>();
+        query.iter(&self.app.world).filter(|t| **t == Themed::Cue).count()
+    }
+
+    /// Cues of the recorded `SoundCueEvent`s
+    fn sound_cues(&self) -> Vec<SoundCue> {
+        self.recorded::<SoundCueEvent>().iter().map(|e| e.cue).collect()
+    }
+
+    /// Number of spawned entities named `name`
+    fn named(&mut self, name: &str) -> usize {
+        let mut query = self.app.world.query::<&Name>();
+        query
+            .iter(&self.app.world)
+            .filter(|n| n.as_str() == name)
+            .count()
+    }
+}
+
+/// 4x4 map with bombs in two opposite corners
+fn corners_map() -> TileMap {
+    TileMap::from_ascii("..1*\n..11\n11..\n*1..").unwrap()
+}
+
+#[test]
+fn creates_board_without_window() {
+    let mut harness = Harness::new(corners_map());
+
+    let board = harness.board();
+    assert_eq!(board.covered_tiles.len(), 16);
+    assert_eq!(board.tile_size, 10.);
+    assert_eq!(board.tile_map[1][0], Tile::BombNeighbor(1));
+    assert!(board.marked_tiles.is_empty());
+
+    let mut bombs = harness.app.world.query_filtered::<&Coordinates, With<Bomb>>();
+    let mut bombs: Vec<Coordinates> = bombs.iter(&harness.app.world).copied().collect();
+    bombs.sort();
+    assert_eq!(bombs, vec![Coordinates { x: 0, y: 0 }, Coordinates { x: 3, y: 3 }]);
+    assert_eq!(harness.named("Tile cover"), 16);
+}
+
+#[test]
+fn uncovers_single_bomb_neighbor() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+
+    assert_eq!(harness.board().covered_tiles.len(), 15);
+    assert!(!harness.board().covered_tiles.contains_key(&Coordinates { x: 1, y: 0 }));
+    assert_eq!(harness.named("Tile cover"), 15);
+    assert!(harness.recorded::<BoardCompletedEvent>().is_empty());
+}
+
+#[test]
+fn empty_tile_cascades_to_completion() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(3, 0);
+
+    let board = harness.board();
+    assert_eq!(board.covered_tiles.len(), 2);
+    assert!(board.is_completed());
+    assert_eq!(harness.recorded::<BoardCompletedEvent>().len(), 1);
+    assert!(harness.recorded::<BombExplosionEvent>().is_empty());
+
+    let summaries = harness.recorded::<GameOverEvent>();
+    assert_eq!(summaries.len(), 1);
+    let summary = summaries[0].0;
+    assert!(summary.won);
+    assert_eq!(summary.clicks, 1);
+    assert_eq!(summary.metrics.three_bv, 1);
+    assert_eq!(summary.efficiency(), 1.);
+    // The bot only plays the board for its difficulty once the game is over
+    assert!(summary.metrics.difficulty.is_some());
+
+    let stats = harness.app.world.resource::<Stats>();
+    let config = &stats.configs["4x4 2 bombs, random"];
+    assert_eq!((config.games, config.wins, config.current_streak), (1, 1, 1));
+    assert!(config.best_time.is_some());
+}
+
+#[test]
+fn bomb_explodes() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(0, 0);
+
+    assert_eq!(harness.recorded::<BombExplosionEvent>().len(), 1);
+    assert!(harness.recorded::<BoardCompletedEvent>().is_empty());
+
+    // Later explosions don't end the game again
+    harness.trigger(3, 3);
+    let summaries = harness.recorded::<GameOverEvent>();
+    assert_eq!(summaries.len(), 1);
+    assert!(!summaries[0].0.won);
+    assert!(harness.app.world.resource::<BoardTimer>().stopped);
+}
+
+#[test]
+fn marked_tile_is_protected() {
+    let mut harness = Harness::new(corners_map());
+    harness.mark(0, 0);
+    assert_eq!(harness.board().marked_tiles, vec![Coordinates { x: 0, y: 0 }]);
+    assert_eq!(harness.named("Flag"), 1);
+
+    harness.trigger(0, 0);
+    assert!(harness.recorded::<BombExplosionEvent>().is_empty());
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+
+    harness.mark(0, 0);
+    assert!(harness.board().marked_tiles.is_empty());
+    assert_eq!(harness.named("Flag"), 0);
+}
+
+#[test]
+fn probability_overlay_follows_the_board() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+    assert_eq!(harness.named("Probability"), 0);
+
+    harness.press(KeyCode::P);
+    assert_eq!(harness.named("Probability"), 15);
+    harness.trigger(0, 1);
+    assert_eq!(harness.named("Probability"), 14);
+    let mut query = harness.app.world.query::<&Probability>();
+    let total: f32 = query.iter(&harness.app.world).map(|p| p.value).sum();
+    assert!((total - 2.).abs() < 1e-4);
+    assert!(query.iter(&harness.app.world).all(|p| !p.approximate));
+
+    harness.press(KeyCode::P);
+    assert_eq!(harness.named("Probability"), 0);
+}
+
+#[test]
+fn flags_keep_the_probability_overlay() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+    harness.press(KeyCode::P);
+    assert_eq!(harness.named("Probability"), 15);
+    // Flagging a cover leaves its overlay in place, even without recomputing it
+    let cover = harness.board().covered_tiles[&Coordinates { x: 0, y: 0 }];
+    let board_assets = harness.app.world.resource::<BoardAssets>().clone();
+    for count in [1, 0] {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &harness.app.world);
+        set_flags(&mut commands, cover, count, &board_assets, 10., None);
+        queue.apply(&mut harness.app.world);
+        assert_eq!(harness.named("Flag"), count);
+        assert_eq!(harness.named("Probability"), 15);
+    }
+}
+
+#[test]
+fn overlay_use_assists_the_game() {
+    let mut harness = Harness::new(corners_map());
+    assert!(!harness.board().overlay_used);
+    // Even shown briefly, the overlay keeps the win out of the high scores
+    harness.press(KeyCode::P);
+    harness.press(KeyCode::P);
+    harness.trigger(3, 0);
+
+    let summary = harness.recorded::<GameOverEvent>()[0].0;
+    assert!(summary.won);
+    assert!(summary.overlay_used && summary.is_assisted());
+    // Preset tile maps aren't seeded
+    assert_eq!(summary.seed, None);
+    let options = harness.app.world.resource::<BoardOptions>();
+    assert_eq!(HighScores::eligible(&summary, options), None);
+}
+
+#[test]
+fn overlay_key_is_ignored_while_typing() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.insert_resource(Typing);
+    harness.press(KeyCode::P);
+    assert_eq!(harness.named("Probability"), 0);
+    assert!(!harness.board().overlay_used);
+
+    harness.app.world.remove_resource::<Typing>();
+    harness.press(KeyCode::P);
+    assert!(harness.board().overlay_used);
+}
+
+#[test]
+fn hint_highlights_forced_move() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+    harness.app.world.send_event(HintRequestEvent);
+    harness.app.update();
+
+    // A single revealed `1` proves nothing
+    let hints = harness.recorded::<HintEvent>();
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].0.kind, HintKind::Guess);
+    assert_eq!(harness.board().hints_used, 1);
+    assert_eq!(harness.named("Hint"), 1);
+
+    harness.trigger(0, 1);
+    assert_eq!(harness.named("Hint"), 0);
+    harness.trigger(1, 1);
+    harness.app.world.send_event(HintRequestEvent);
+    harness.app.update();
+    // Three `1`s around the corner bomb give a certain move
+    let hint = harness.recorded::<HintEvent>()[1].0.clone();
+    assert_ne!(hint.kind, HintKind::Guess);
+    assert_eq!(hint.kind == HintKind::Mine, harness.board().tile_map.is_bomb_at(hint.coordinates));
+    assert_eq!(harness.named("Hint"), 1);
+    assert_eq!(harness.named("Hint proof"), hint.proof.len());
+    assert!(!hint.proof.is_empty());
+    assert_eq!(harness.board().hints_used, 2);
+}
+
+#[test]
+fn pause_hides_board_and_freezes_timer() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.update();
+    let board_entity = harness.board().entity;
+    let is_visible = |harness: &Harness| harness.app.world.get::<Visibility>(board_entity).unwrap().is_visible;
+
+    let mut state = harness.app.world.resource_mut::<State<TestState>>();
+    state.push(TestState::Paused).unwrap();
+    harness.app.update();
+    assert!(!is_visible(&harness));
+    let elapsed = harness.app.world.resource::<BoardTimer>().elapsed;
+    std::thread::sleep(Duration::from_millis(5));
+    harness.trigger(1, 0);
+    assert_eq!(harness.app.world.resource::<BoardTimer>().elapsed, elapsed);
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+
+    let mut state = harness.app.world.resource_mut::<State<TestState>>();
+    state.pop().unwrap();
+    harness.app.update();
+    assert!(is_visible(&harness));
+    assert!(harness.app.world.resource::<BoardTimer>().elapsed > elapsed);
+    harness.trigger(1, 0);
+    assert_eq!(harness.board().covered_tiles.len(), 15);
+}
+
+#[test]
+fn theme_switch_restyles_the_board() {
+    let mut harness = Harness::new(corners_map());
+    harness.mark(0, 0);
+    harness.trigger(1, 0);
+
+    let mut board_assets = harness.app.world.resource_mut::<BoardAssets>();
+    board_assets.covered_tile_material.color = Color::BLUE;
+    board_assets.flag_material.color = Color::RED;
+    board_assets.bomb_counter_colors = vec![Color::PINK];
+    harness.app.update();
+
+    let mut sprites = harness.app.world.query::<(&Themed, &Sprite)>();
+    let colors: Vec<_> = sprites.iter(&harness.app.world).map(|(t, s)| (*t, s.color)).collect();
+    assert_eq!(colors.iter().filter(|(t, c)| *t == Themed::Cover && *c == Color::BLUE).count(), 15);
+    assert!(colors.contains(&(Themed::Flag, Color::RED)));
+    let mut texts = harness.app.world.query_filtered::<&Text, With<Themed>>();
+    assert!(texts.iter(&harness.app.world).all(|t| t.sections[0].style.color == Color::PINK));
+    // Restyling doesn't touch the game
+    assert_eq!(harness.board().covered_tiles.len(), 15);
+}
+
+#[test]
+fn number_style_respawns_cues() {
+    let mut harness = Harness::new(corners_map());
+    assert_eq!(harness.cues(), 0);
+
+    let mut number_style = harness.app.world.resource_mut::<NumberStyle>();
+    number_style.cue = NumberCue::Pips;
+    number_style.palette = ColorPalette::Deuteranopia;
+    harness.app.update();
+    // Every counter of the corners map is a 1
+    assert_eq!(harness.cues(), 6);
+    let one = ColorPalette::Deuteranopia.colors().unwrap()[0];
+    let mut texts = harness.app.world.query::<(&Themed, &Text)>();
+    assert!(texts
+        .iter(&harness.app.world)
+        .filter(|(t, _)| **t == Themed::Counter)
+        .all(|(_, text)| text.sections[0].style.color == one));
+
+    harness.app.world.resource_mut::<NumberStyle>().cue = NumberCue::Outline;
+    harness.app.update();
+    assert_eq!(harness.cues(), 6 * 4);
+}
+
+#[test]
+fn reveal_animation_never_delays_the_game() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<AnimationSettings>().reveal = Duration::from_millis(20);
+    harness.trigger(3, 0);
+
+    // The board is completed while the covers are still fading
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.recorded::<BoardCompletedEvent>().len(), 1);
+    assert_eq!(harness.named("Revealed cover"), 14);
+    let mut tweens = harness.app.world.query::<&Tween>();
+    let delays: Vec<f32> = tweens.iter(&harness.app.world).map(|t| t.delay).collect();
+    // Later rings of the cascade start later
+    assert!(delays.iter().any(|d| *d > 0.));
+
+    harness.wait(Duration::from_millis(200));
+    assert_eq!(harness.named("Revealed cover"), 0);
+    assert_eq!(harness.named("Tile cover"), 2);
+}
+
+#[test]
+fn explosion_shakes_and_flashes_the_board() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<AnimationSettings>().explosion = Duration::from_millis(20);
+    let board_entity = harness.board().entity;
+    let origin = harness.app.world.get::<Transform>(board_entity).unwrap().translation;
+    harness.trigger(0, 0);
+    assert_eq!(harness.named("Explosion flash"), 1);
+    assert!(harness.app.world.get::<Tween>(board_entity).is_some());
+
+    harness.wait(Duration::from_millis(100));
+    assert_eq!(harness.named("Explosion flash"), 0);
+    assert!(harness.app.world.get::<Tween>(board_entity).is_none());
+    assert_eq!(harness.app.world.get::<Transform>(board_entity).unwrap().translation, origin);
+}
+
+#[test]
+fn disabled_animations_are_instant() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<AnimationSettings>().enabled = false;
+    harness.trigger(1, 0);
+    harness.mark(0, 0);
+    harness.trigger(0, 0);
+    assert_eq!(harness.named("Revealed cover"), 0);
+    assert_eq!(harness.named("Explosion flash"), 0);
+    let mut tweens = harness.app.world.query::<&Tween>();
+    assert_eq!(tweens.iter(&harness.app.world).count(), 0);
+}
+
+#[test]
+fn sound_cues_follow_board_events() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<SoundSettings>().volume = 0.25;
+    harness.trigger(1, 0);
+    harness.mark(0, 0);
+    harness.mark(0, 0);
+    harness.trigger(3, 0);
+    assert_eq!(
+        harness.sound_cues(),
+        vec![SoundCue::Reveal, SoundCue::Flag, SoundCue::Unflag, SoundCue::Cascade, SoundCue::Win]
+    );
+    assert!(harness.recorded::<SoundCueEvent>().iter().all(|e| e.volume == 0.25));
+
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(0, 0);
+    assert_eq!(harness.sound_cues(), vec![SoundCue::Explosion]);
+}
+
+#[test]
+fn cascade_sound_cues_are_rate_limited() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<SoundSettings>().min_interval = Duration::ZERO;
+    harness.trigger(3, 0);
+    // Every ring of the cascade has its cue
+    let cascades = harness.sound_cues().iter().filter(|c| **c == SoundCue::Cascade).count();
+    assert!(cascades > 1);
+
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<SoundSettings>().min_interval = Duration::from_secs(3600);
+    harness.trigger(3, 0);
+    assert_eq!(harness.sound_cues(), vec![SoundCue::Cascade, SoundCue::Win]);
+}
+
+#[test]
+fn muted_board_sends_no_sound_cue() {
+    let mut harness = Harness::new(corners_map());
+    harness.app.world.resource_mut::<SoundSettings>().muted = true;
+    harness.trigger(1, 0);
+    harness.mark(0, 0);
+    harness.trigger(3, 0);
+    assert!(harness.recorded::<SoundCueEvent>().is_empty());
+}
+
+#[test]
+fn undo_reverts_a_whole_cascade() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+    harness.mark(1, 1);
+    harness.mark(2, 2);
+    let covered = harness.covered();
+    // The cascade uncovers both wrongly marked tiles
+    harness.trigger(3, 0);
+    assert!(harness.board().is_completed());
+    assert!(harness.board().marked_tiles.is_empty());
+
+    harness.undo();
+    assert_eq!(harness.covered(), covered);
+    assert_eq!(harness.board().marked_tiles, vec![Coordinates { x: 1, y: 1 }, Coordinates { x: 2, y: 2 }]);
+    assert_eq!(harness.named("Tile cover"), 15);
+    assert_eq!(harness.named("Flag"), 2);
+    assert!(!harness.app.world.resource::<BoardTimer>().stopped);
+
+    harness.redo();
+    assert!(harness.board().is_completed());
+    assert!(harness.board().marked_tiles.is_empty());
+    assert_eq!(harness.named("Tile cover"), 2);
+    assert_eq!(harness.named("Flag"), 0);
+    assert!(harness.app.world.resource::<BoardTimer>().stopped);
+    // The game was over before the undo, it's recorded once
+    assert_eq!(harness.recorded::<GameOverEvent>().len(), 1);
+
+    harness.undo();
+    harness.undo();
+    harness.undo();
+    harness.undo();
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+    assert!(harness.board().marked_tiles.is_empty());
+    assert_eq!(harness.named("Flag"), 0);
+}
+
+#[test]
+fn undo_fatal_click_marks_the_game() {
+    let mut harness = Harness::new(corners_map());
+    harness.mark(1, 0);
+    harness.trigger(0, 0);
+    assert_eq!(harness.recorded::<BombExplosionEvent>().len(), 1);
+
+    harness.undo();
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+    assert!(!harness.board().is_exploded());
+    assert_eq!(harness.board().marked_tiles, vec![Coordinates { x: 1, y: 0 }]);
+    assert!(!harness.app.world.resource::<BoardTimer>().stopped);
+    let stats = harness.app.world.resource::<Stats>();
+    let config = &stats.configs["4x4 2 bombs, random"];
+    assert_eq!((config.games, config.wins, config.undo_games), (1, 0, 1));
+
+    // Undone then won, the loss stays the result of the game
+    harness.trigger(3, 0);
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.recorded::<GameOverEvent>().len(), 1);
+}
+
+#[test]
+fn undone_win_is_assisted() {
+    let mut harness = Harness::new(corners_map());
+    harness.mark(0, 0);
+    harness.undo();
+    assert!(harness.board().marked_tiles.is_empty());
+    assert_eq!(harness.named("Flag"), 0);
+    harness.redo();
+    assert_eq!(harness.board().marked_tiles, vec![Coordinates { x: 0, y: 0 }]);
+    assert_eq!(harness.named("Flag"), 1);
+
+    harness.trigger(3, 0);
+    let summary = harness.recorded::<GameOverEvent>()[0].0;
+    assert!(summary.won);
+    assert_eq!(summary.undos, 1);
+    assert!(summary.is_assisted());
+    let stats = harness.app.world.resource::<Stats>();
+    let config = &stats.configs["4x4 2 bombs, random"];
+    assert_eq!((config.wins, config.undo_games, config.best_time), (1, 1, None));
+}
+
+#[test]
+fn chord_is_undone_in_one_step() {
+    let mut harness = Harness::new(corners_map());
+    harness.trigger(1, 0);
+    harness.mark(0, 0);
+    // Clicking the uncovered counter with its bomb marked uncovers its other neighbors
+    harness.trigger(1, 0);
+    assert!(harness.board().is_completed());
+
+    harness.undo();
+    assert_eq!(harness.board().covered_tiles.len(), 15);
+    assert_eq!(harness.board().marked_tiles, vec![Coordinates { x: 0, y: 0 }]);
+}
+
+#[test]
+fn hexagonal_board_counts_and_cascades() {
+    let bomb = Coordinates { x: 2, y: 1 };
+    let tile_map = TileMap::from_mines(5, 4, &[bomb]).unwrap().with_topology(Topology::Hexagonal);
+    let mut harness = Harness::new(tile_map);
+
+    let board = harness.board();
+    // (1, 0) touches the bomb on a square grid only
+    assert_eq!(board.tile_map[0][1], Tile::Empty);
+    assert_eq!(board.tile_map[0][3], Tile::BombNeighbor(1));
+    let numbers = board.tile_map.iter().flatten().filter(|t| matches!(t, Tile::BombNeighbor(_))).count();
+    assert_eq!(numbers, 6);
+
+    // Odd rows are shifted right by half a tile
+    let mut tiles = harness.app.world.query::<(&Coordinates, &Transform)>();
+    let mut x = |harness: &mut Harness, coords: Coordinates| {
+        let (_, transform) = tiles.iter(&harness.app.world).find(|(c, _)| **c == coords).unwrap();
+        transform.translation.x
+    };
+    assert_eq!(x(&mut harness, Coordinates { x: 0, y: 0 }), 5.);
+    assert_eq!(x(&mut harness, Coordinates { x: 0, y: 1 }), 10.);
+
+    harness.trigger(0, 3);
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.covered(), vec![bomb]);
+    assert_eq!(harness.recorded::<BoardCompletedEvent>().len(), 1);
+}
+
+#[test]
+fn wrapped_board_cascades_across_the_edges() {
+    let bomb = Coordinates { x: 0, y: 0 };
+    let tile_map = TileMap::from_mines(4, 4, &[bomb]).unwrap().with_wrap(true);
+    let mut harness = Harness::new(tile_map);
+    // The opposite corner touches the bomb across both edges
+    assert_eq!(harness.board().tile_map[3][3], Tile::BombNeighbor(1));
+    assert_eq!(harness.board().metrics.three_bv, 1);
+
+    harness.trigger(2, 2);
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.covered(), vec![bomb]);
+}
+
+#[test]
+fn knight_board_cascades_by_knight_moves() {
+    let bomb = Coordinates { x: 0, y: 0 };
+    let tile_map = TileMap::from_mines(5, 5, &[bomb])
+        .unwrap()
+        .with_neighborhood(NeighborhoodRule::Knight);
+    let mut harness = Harness::new(tile_map);
+    // Only the two knight moves away from the bomb are numbered
+    assert_eq!(harness.board().tile_map[1][1], Tile::Empty);
+    assert_eq!(harness.board().tile_map[2][1], Tile::BombNeighbor(1));
+    assert_eq!(harness.board().tile_map[1][2], Tile::BombNeighbor(1));
+
+    harness.trigger(4, 4);
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.covered(), vec![bomb]);
+}
+
+#[test]
+fn multi_mine_tile_takes_stacked_flags() {
+    let bomb = Coordinates { x: 0, y: 0 };
+    let tile_map = TileMap::from_multi_mines(4, 1, &[bomb, bomb]).unwrap();
+    let mut harness = Harness::new(tile_map);
+    assert_eq!(harness.board().tile_map[0][1], Tile::BombNeighbor(2));
+
+    harness.mark(0, 0);
+    harness.mark(0, 0);
+    assert_eq!(harness.board().marks_at(&bomb), 2);
+    assert_eq!(harness.named("Flag"), 2);
+    harness.undo();
+    assert_eq!(harness.named("Flag"), 1);
+    harness.redo();
+    harness.mark(0, 0);
+    assert_eq!(harness.named("Flag"), 3);
+    // Marking past the most bombs a tile holds clears its flags
+    harness.mark(0, 0);
+    assert_eq!(harness.board().marks_at(&bomb), 0);
+    assert_eq!(harness.named("Flag"), 0);
+
+    // A single tile holds both bombs
+    harness.trigger(3, 0);
+    assert!(harness.board().is_completed());
+    assert_eq!(harness.covered(), vec![bomb]);
+}
+
+#[test]
+fn daily_result_is_recorded_once() {
+    let mut harness = Harness::new(corners_map());
+    let date = Date::from_days(20_000);
+    harness.app.world.resource_mut::<BoardOptions>().daily = Some(date);
+    harness.trigger(3, 0);
+
+    let history = harness.app.world.resource::<DailyHistory>();
+    assert_eq!(history.results.len(), 1);
+    assert!(history.results[0].won);
+    assert_eq!(history.current_streak(date), 1);
+    let stats = harness.app.world.resource::<Stats>();
+    assert!(stats.configs.contains_key("4x4 2 bombs, daily"));
+}
+
+/// Harness on the corners map with `lives` in lives mode
+#[test]
+fn bomb_costs_a_life_and_gets_flagged() {
+    let options = BoardOptions {
+        lives: Some(2),
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.trigger(0, 0);
+    assert_eq!(harness.recorded::<BombExplosionEvent>().len(), 1);
+    assert!(harness.recorded::<GameOverEvent>().is_empty());
+    assert_eq!(harness.board().lives, Some(1));
+    assert_eq!(harness.board().marks_at(&Coordinates { x: 0, y: 0 }), 1);
+    assert_eq!(harness.named("Detonated flags"), 1);
+    assert_eq!(harness.named("Flag"), 1);
+    assert!(!harness.app.world.resource::<BoardTimer>().stopped);
+
+    harness.undo();
+    assert_eq!(harness.board().lives, Some(2));
+    assert!(harness.board().detonated.is_empty());
+    assert_eq!(harness.named("Detonated flags"), 0);
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+    harness.redo();
+    assert_eq!(harness.board().lives, Some(1));
+    assert_eq!(harness.named("Detonated flags"), 1);
+
+    // The detonated bomb doesn't keep the board from being completed
+    harness.trigger(3, 0);
+    let summaries = harness.recorded::<GameOverEvent>();
+    assert_eq!(summaries.len(), 1);
+    assert!(summaries[0].0.won);
+    let stats = harness.app.world.resource::<Stats>();
+    assert!(stats.configs.keys().all(|k| k.ends_with(", 2 lives")));
+}
+
+#[test]
+fn last_life_ends_the_game() {
+    let options = BoardOptions {
+        lives: Some(2),
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.trigger(0, 0);
+    harness.trigger(3, 3);
+    assert_eq!(harness.recorded::<BombExplosionEvent>().len(), 2);
+    assert_eq!(harness.board().lives, Some(0));
+    assert!(harness.board().is_exploded());
+    let summaries = harness.recorded::<GameOverEvent>();
+    assert_eq!(summaries.len(), 1);
+    assert!(!summaries[0].0.won);
+
+    harness.undo();
+    assert_eq!(harness.board().lives, Some(1));
+    assert!(!harness.board().is_exploded());
+}
+
+/// Harness on the corners map running a `mode` run
+#[test]
+fn mine_race_penalizes_wrong_flags() {
+    let options = BoardOptions {
+        mode: GameMode::MineRace,
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.mark(1, 1);
+    assert_eq!(harness.board().marks_at(&Coordinates { x: 1, y: 1 }), 0);
+    assert_eq!(harness.app.world.resource::<ModeRun>().wrong_flags, 1);
+    assert!(harness.app.world.resource::<BoardTimer>().elapsed >= Duration::from_secs(10));
+
+    harness.mark(0, 0);
+    assert!(harness.recorded::<GameOverEvent>().is_empty());
+    harness.mark(3, 3);
+    let summaries = harness.recorded::<GameOverEvent>();
+    assert_eq!(summaries.len(), 1);
+    assert!(summaries[0].0.won);
+    let runs = harness.recorded::<ModeOverEvent>();
+    assert_eq!(runs.len(), 1);
+    assert!(runs[0].0.score.is_some_and(|s| s >= 10.));
+    let stats = harness.app.world.resource::<Stats>();
+    assert!(stats.configs.is_empty());
+    assert!(stats.modes.keys().all(|k| k.starts_with("mine race, ")));
+}
+
+#[test]
+fn time_attack_moves_on_to_the_next_board() {
+    let options = BoardOptions {
+        mode: GameMode::TimeAttack,
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.trigger(3, 0);
+    harness.app.update();
+    assert_eq!(harness.app.world.resource::<ModeRun>().boards_cleared, 1);
+    assert_eq!(harness.board().covered_tiles.len(), 16);
+    assert!(!harness.board().game_over);
+    assert!(harness.recorded::<ModeOverEvent>().is_empty());
+
+    harness.trigger(0, 0);
+    let runs = harness.recorded::<ModeOverEvent>();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].0.score, Some(1.));
+    let stats = harness.app.world.resource::<Stats>();
+    assert!(stats.modes.keys().all(|k| k.starts_with("time attack 3:00, ")));
+    assert_eq!(stats.modes.values().next().map(|m| m.best), Some(Some(1.)));
+}
+
+#[test]
+fn time_attack_ends_with_the_time_limit() {
+    let options = BoardOptions {
+        mode: GameMode::TimeAttack,
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.app.world.resource_mut::<BoardTimer>().elapsed = Duration::from_secs(180);
+    harness.app.update();
+    assert!(harness.board().game_over);
+    assert!(harness.app.world.resource::<BoardTimer>().stopped);
+    let runs = harness.recorded::<ModeOverEvent>();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].0.score, Some(0.));
+    assert!(harness.recorded::<GameOverEvent>().is_empty());
+}
+
+/// Harness on the corners map running a versus game of `mode`
+fn turn(harness: &Harness) -> Player {
+    harness.app.world.resource::<VersusGame>().turn
+}
+
+#[test]
+fn versus_scores_flagged_bombs() {
+    let options = BoardOptions {
+        mode: GameMode::Versus,
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.trigger(3, 0);
+    assert_eq!(harness.covered().len(), 2);
+    assert_eq!(turn(&harness), Player::One);
+
+    harness.mark(0, 0);
+    assert_eq!(harness.app.world.resource::<VersusGame>().scores, [1, 0]);
+    assert_eq!(turn(&harness), Player::Two);
+    assert!(!harness.board().is_exploded());
+    // A revealed bomb goes to the opponent
+    harness.trigger(3, 3);
+    assert_eq!(harness.named("Detonated flags"), 2);
+    let over = harness.recorded::<VersusOverEvent>();
+    assert_eq!(over.len(), 1);
+    assert_eq!((over[0].scores, over[0].winner), ([2, 0], Some(Player::One)));
+    assert!(harness.board().game_over);
+    assert!(harness.recorded::<GameOverEvent>().is_empty());
+    assert!(harness.recorded::<BombExplosionEvent>().is_empty());
+}
+
+#[test]
+fn versus_flags_scores_revealed_bombs() {
+    let options = BoardOptions {
+        mode: GameMode::Flags,
+        ..Default::default()
+    };
+    let mut harness = Harness::with_options(corners_map(), options);
+    harness.trigger(0, 0);
+    assert_eq!(turn(&harness), Player::One);
+    harness.mark(3, 3);
+    assert_eq!(harness.board().marks_at(&Coordinates { x: 3, y: 3 }), 0);
+    harness.trigger(3, 0);
+    assert_eq!(turn(&harness), Player::Two);
+    harness.trigger(3, 3);
+
+    let mut query = harness.app.world.query::<&Themed>();
+    let flags: Vec<Themed> = query
+        .iter(&harness.app.world)
+        .filter(|t| matches!(t, Themed::PlayerFlag(_)))
+        .copied()
+        .collect();
+    assert!(flags.contains(&Themed::PlayerFlag(Player::One)));
+    assert!(flags.contains(&Themed::PlayerFlag(Player::Two)));
+    let over = harness.recorded::<VersusOverEvent>();
+    assert_eq!(over.len(), 1);
+    assert_eq!((over[0].scores, over[0].winner), ([1, 1], None));
+    // Nothing to undo
+    harness.undo();
+    assert_eq!(harness.covered().len(), 0);
+}
+
+/// App running `EndlessPlugin` without a window, with the view around the origin
+fn endless_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin::default())
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_state(TestState::InGame)
+        .insert_resource(EndlessOptions {
+            seed: Some(seed),
+            tile_size: 10.,
+            margin: 0,
+            view_size: Vec2::new(100., 100.),
+            ..Default::default()
+        })
+        .insert_resource(Harness::board_assets())
+        .add_plugin(EndlessPlugin {
+            running_state: TestState::InGame,
+        });
+    Harness::record::<EndlessGameOverEvent>(&mut app);
+    app.update();
+    app
+}
+
+#[test]
+fn endless_board_streams_chunks_and_ends_on_a_bomb() {
+    let mut app = endless_app(9);
+    let origin = WorldCoordinates::default();
+    let board = app.world.resource::<EndlessBoard>();
+    // The 4 chunks around the origin
+    assert_eq!(board.spawned_chunks.len(), 4);
+    assert_eq!(board.covers.len(), 4 * 256 - board.cleared as usize);
+    assert!(!board.covers.contains_key(&origin));
+    let cleared = board.cleared;
+
+    let mut board = app.world.resource_mut::<EndlessBoard>();
+    let mut covered: Vec<WorldCoordinates> = board.covers.keys().copied().collect();
+    covered.sort();
+    let safe = *covered.iter().find(|c| !board.is_bomb_at(**c)).unwrap();
+    let bomb = *covered.iter().find(|c| board.is_bomb_at(**c)).unwrap();
+
+    app.world.send_event(EndlessMarkEvent(safe));
+    app.update();
+    let mut flags = app.world.query::<&Name>();
+    assert_eq!(flags.iter(&app.world).filter(|n| n.as_str() == "Flag").count(), 1);
+    // Flagged tiles aren't revealed
+    app.world.send_event(EndlessTriggerEvent(safe));
+    app.update();
+    assert!(app.world.resource::<EndlessBoard>().covers.contains_key(&safe));
+    app.world.send_event(EndlessMarkEvent(safe));
+    app.update();
+    app.world.send_event(EndlessTriggerEvent(safe));
+    app.update();
+    let board = app.world.resource::<EndlessBoard>();
+    assert!(board.cleared > cleared);
+    assert!(!board.covers.contains_key(&safe));
+
+    app.world.send_event(EndlessTriggerEvent(bomb));
+    app.update();
+    let cleared = app.world.resource::<EndlessBoard>().cleared;
+    assert_eq!(app.world.resource::<EndlessBoard>().exploded, Some(bomb));
+    let game_overs = &app.world.resource::<Recorded<EndlessGameOverEvent>>().0;
+    assert_eq!(game_overs.len(), 1);
+    assert_eq!(game_overs[0].cleared, cleared);
+    assert_eq!(app.world.resource::<Stats>().endless_best, cleared);
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+use board_plugin::events::SoundCueEvent;
+use board_plugin::resources::BoardAssets;
+
+/// Plays the sound of every cue sent by the board, from the current theme
+pub fn play_sound_cues(
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    board_assets: Res<BoardAssets>,
+    mut sound_cue_evr: EventReader<SoundCueEvent>,
+) {
+    for event in sound_cue_evr.iter() {
+        if let Some(path) = board_assets.sounds.get(&event.cue) {
+            let sound = asset_server.load(path.as_str());
+            audio.play_with_settings(sound, PlaybackSettings::ONCE.with_volume(event.volume));
+        }
+    }
+}
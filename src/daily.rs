@@ -0,0 +1,78 @@
+use std::fs;
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{Board, BoardOptions, DailyHistory, Typing};
+
+/// Loads the daily history from the user's data directory, in memory only if that fails
+pub fn load_daily_history() -> DailyHistory {
+    let path = match DailyHistory::default_path() {
+        Some(p) => p,
+        None => {
+            log::warn!("No data directory, daily results won't be saved");
+            return DailyHistory::default();
+        }
+    };
+    match DailyHistory::load(&path) {
+        Ok(history) => history,
+        Err(e) => {
+            log::error!("Failed to load the daily history from {}: {}", path.display(), e);
+            DailyHistory::default()
+        }
+    }
+}
+
+/// Shares the result of a finished daily challenge, `C` copies it to the clipboard
+/// and `F` writes it to a file
+pub fn share_daily_result(
+    keys: Res<Input<KeyCode>>,
+    options: Res<BoardOptions>,
+    board: Option<Res<Board>>,
+    history: Res<DailyHistory>,
+    typing: Option<Res<Typing>>,
+) {
+    let date = match options.daily {
+        None => return,
+        Some(d) => d,
+    };
+    if typing.is_some() || !board.is_some_and(|b| b.game_over) {
+        return;
+    }
+    let text = match history.share_text(date) {
+        None => return,
+        Some(t) => t,
+    };
+    if keys.just_pressed(KeyCode::C) {
+        copy_to_clipboard(&text);
+    }
+    if keys.just_pressed(KeyCode::F) {
+        let path = match DailyHistory::share_path(date) {
+            Some(p) => p,
+            None => {
+                log::warn!("No data directory to write the daily result to");
+                return;
+            }
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, format!("{}\n", text)));
+        match written {
+            Ok(()) => log::info!("Daily result written to {}", path.display()),
+            Err(e) => log::error!("Failed to write the daily result to {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str) {
+    let copied = arboard::Clipboard::new().and_then(|mut c| c.set_text(text.to_string()));
+    match copied {
+        Ok(()) => log::info!("Daily result copied to the clipboard"),
+        Err(e) => log::error!("Failed to copy the daily result: {}", e),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str) {
+    log::warn!("Built without clipboard support, press F to write the result to a file");
+}
@@ -0,0 +1,157 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::date::Date;
+use board_plugin::events::GameOverEvent;
+use board_plugin::resources::{BoardOptions, Difficulty, HighScore, HighScores, Typing};
+
+/// Pending high score waiting for the player's name, on the root node of the prompt
+#[derive(Debug, Component)]
+pub struct NameEntry {
+    pub difficulty: Difficulty,
+    pub score: HighScore,
+}
+
+/// Text showing the name being typed
+#[derive(Component)]
+pub struct NamePromptText;
+
+/// Loads the high scores from the user's data directory, in memory only if that fails
+pub fn load_high_scores() -> HighScores {
+    let path = match HighScores::default_path() {
+        Some(p) => p,
+        None => {
+            log::warn!("No data directory, high scores won't be saved");
+            return HighScores::default();
+        }
+    };
+    match HighScores::load(&path) {
+        Ok(scores) => scores,
+        Err(e) => {
+            log::error!("Failed to load high scores from {}: {}", path.display(), e);
+            HighScores::default()
+        }
+    }
+}
+
+/// Asks for the player's name when a win makes the high score table
+pub fn prompt_name(
+    mut commands: Commands,
+    board_options: Res<BoardOptions>,
+    high_scores: Res<HighScores>,
+    asset_server: Res<AssetServer>,
+    mut game_over_evr: EventReader<GameOverEvent>,
+) {
+    for GameOverEvent(summary) in game_over_evr.iter() {
+        let difficulty = match HighScores::eligible(summary, &board_options) {
+            Some(d) => d,
+            None => continue,
+        };
+        let time = summary.elapsed.as_secs_f32();
+        let rank = match high_scores.rank(difficulty, time) {
+            Some(r) => r,
+            None => continue,
+        };
+        log::info!("New {} high score, rank {}", difficulty, rank + 1);
+        // Keeps the game keys, like the overlay toggle, out of the name
+        commands.insert_resource(Typing);
+        let entry = NameEntry {
+            difficulty,
+            score: HighScore {
+                name: String::new(),
+                time,
+                date: Date::today(),
+                seed: summary.seed.unwrap_or_default(),
+            },
+        };
+        spawn_prompt(&mut commands, &asset_server, entry, rank);
+    }
+}
+
+/// Edits the name with the keyboard, `Enter` saves the score and `Escape` discards it
+pub fn enter_name(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut high_scores: ResMut<HighScores>,
+    mut received_character_evr: EventReader<ReceivedCharacter>,
+    mut prompts: Query<(Entity, &mut NameEntry)>,
+    mut texts: Query<&mut Text, With<NamePromptText>>,
+) {
+    // Always read the characters so keys typed before the prompt don't end up in the name
+    let typed: Vec<char> = received_character_evr.iter().map(|e| e.char).collect();
+    let (prompt, mut entry) = match prompts.iter_mut().next() {
+        Some(p) => p,
+        None => return,
+    };
+
+    if keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::Escape) {
+        if keys.just_pressed(KeyCode::Return) {
+            let mut score = entry.score.clone();
+            if score.name.trim().is_empty() {
+                score.name = "Anonymous".to_string();
+            }
+            high_scores.insert(entry.difficulty, score);
+            if let Err(e) = high_scores.save() {
+                log::error!("Failed to save high scores: {}", e);
+            }
+        }
+        commands.entity(prompt).despawn_recursive();
+        commands.remove_resource::<Typing>();
+        return;
+    }
+
+    let name = &mut entry.score.name;
+    if keys.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+    for c in typed.into_iter().filter(|c| !c.is_control()) {
+        if name.chars().count() < HighScores::MAX_NAME_LENGTH {
+            name.push(c);
+        }
+    }
+    if entry.is_changed() {
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = format!("{}_", entry.score.name);
+        }
+    }
+}
+
+fn spawn_prompt(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    entry: NameEntry,
+    rank: usize,
+) {
+    let title = format!(
+        "New {} high score: #{} in {:.2}s\nEnter your name",
+        entry.difficulty,
+        rank + 1,
+        entry.score.time
+    );
+    let style = TextStyle {
+        font: asset_server.load("fonts/pixeled.ttf"),
+        font_size: 14.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            z_index: ZIndex::Global(20),
+            ..Default::default()
+        })
+        .insert(Name::new("Name prompt"))
+        .insert(entry)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, style.clone()));
+            parent
+                .spawn(TextBundle::from_section("_", style))
+                .insert(NamePromptText);
+        });
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use board_plugin::resources::{Board, BoardOptions, BoardTimer, EndlessBoard, GameMode, ModeRun, Player, VersusGame};
+
+/// Root node of the in-game heads-up display
+#[derive(Component)]
+pub struct Hud;
+
+/// Text of the HUD describing the running board
+#[derive(Component)]
+pub struct HudText;
+
+pub fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(8.),
+                    top: Val::Px(4.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            z_index: ZIndex::Global(1),
+            ..Default::default()
+        })
+        .insert(Name::new("HUD"))
+        .insert(Hud)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/pixeled.ttf"),
+                        font_size: 12.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(HudText);
+        });
+}
+
+/// Shows the rules of the board, the lives left, the progress of a time attack or mine
+/// race and the scores of a versus game, the board is created after the HUD
+pub fn update_hud(
+    board: Option<Res<Board>>,
+    timer: Option<Res<BoardTimer>>,
+    run: Option<Res<ModeRun>>,
+    versus: Option<Res<VersusGame>>,
+    options: Res<BoardOptions>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let board = match board {
+        None => return,
+        Some(b) => b,
+    };
+    let mut value = format!("Neighbors: {}", board.tile_map.neighborhood());
+    if let Some(lives) = board.lives {
+        value = format!("{} - Lives: {}", value, lives);
+    }
+    if let (Some(run), Some(timer)) = (run, timer) {
+        value = format!("{} - {}", run_text(&run, &board, &timer, &options), value);
+    }
+    if let Some(versus) = versus {
+        value = format!("{} - {}", versus_text(&versus), value);
+    }
+    if let Some(date) = options.daily {
+        value = if board.game_over {
+            format!("Daily {} - C: copy result, F: save it", date)
+        } else {
+            format!("Daily {} - {}", date, value)
+        };
+    }
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+/// Progress of a time attack or mine race
+fn run_text(run: &ModeRun, board: &Board, timer: &BoardTimer, options: &BoardOptions) -> String {
+    match run.mode {
+        GameMode::TimeAttack if run.over => format!("Time attack over, {} boards", run.boards_cleared),
+        GameMode::TimeAttack => {
+            let left = run.time_left(options.time_limit, timer.elapsed).as_secs();
+            format!("{}:{:02} left, {} boards", left / 60, left % 60, run.boards_cleared)
+        }
+        GameMode::MineRace => format!(
+            "Flags {}/{}, {} wrong, {:.1}s",
+            board.marked_tiles.len(),
+            board.tile_map.bomb_count(),
+            run.wrong_flags,
+            timer.elapsed.as_secs_f32()
+        ),
+        GameMode::Classic | GameMode::Versus | GameMode::Flags => String::new(),
+    }
+}
+
+/// Scores of a versus game and whose turn it is, or who won
+fn versus_text(versus: &VersusGame) -> String {
+    let scores = format!("P1 {} - P2 {}", versus.score(Player::One), versus.score(Player::Two));
+    if !versus.over {
+        return format!("{} - {}'s turn", scores, versus.turn);
+    }
+    match versus.leader() {
+        Some(player) => format!("{} - {} wins!", scores, player),
+        None => format!("{} - Draw!", scores),
+    }
+}
+
+/// Shows the score of the endless game and how it ended
+pub fn update_endless_hud(board: Option<Res<EndlessBoard>>, mut texts: Query<&mut Text, With<HudText>>) {
+    let board = match board {
+        None => return,
+        Some(b) => b,
+    };
+    let value = match board.exploded {
+        None => format!("Cleared: {} - arrows to move", board.cleared),
+        Some(_) => format!("Boom! Cleared: {} - Escape for the menu", board.cleared),
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub fn despawn_hud(mut commands: Commands, huds: Query<Entity, With<Hud>>) {
+    for hud in huds.iter() {
+        commands.entity(hud).despawn_recursive();
+    }
+}
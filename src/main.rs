@@ -1,16 +1,31 @@
 use bevy::log;
 use bevy::prelude::*;
-use board_plugin::BoardPlugin;
-use board_plugin::resources::{BoardAssets, BoardOptions, SpriteMaterial};
+use board_plugin::{BoardPlugin, EndlessPlugin};
+use board_plugin::events::{HintRequestEvent, RedoEvent, UndoEvent};
+use board_plugin::resources::{BoardOptions, Difficulty, EndlessOptions, Typing};
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 
+#[cfg(feature = "audio")]
+mod audio;
+mod daily;
+mod high_scores;
+mod hud;
+mod menu;
+mod pause;
+mod stats_screen;
+mod themes;
+
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
+    Menu,
     InGame,
-    Out,
+    /// Pushed on top of `InGame`
+    Paused,
+    /// Unbounded board explored with the arrow keys
+    Endless,
 }
 
 fn camera_setup(mut commands: Commands) {
@@ -35,83 +50,119 @@ fn main() {
     #[cfg(feature = "debug")]
     app.add_plugin(WorldInspectorPlugin::new());
 
-    // Board plugin options
+    // Stats persisted across sessions
+    app.insert_resource(stats_screen::load_stats());
+    app.insert_resource(high_scores::load_high_scores());
+    app.insert_resource(daily::load_daily_history());
+
+    // Board plugin options, the menu replaces the size and bombs before each game
+    app.insert_resource(BoardOptions {
+        tile_padding: 1.,
+        ..BoardOptions::from_difficulty(Difficulty::Beginner)
+    });
     app.add_plugin(BoardPlugin {
         running_state: AppState::InGame,
     });
-    app.add_state(AppState::Out);
+    // Endless games are saved when leaving them and resumed from the menu
+    app.insert_resource(EndlessOptions {
+        save_dir: EndlessOptions::default_save_dir(),
+        ..Default::default()
+    });
+    app.add_plugin(EndlessPlugin {
+        running_state: AppState::Endless,
+    });
+    app.add_state(AppState::Menu);
+
+    app.init_resource::<menu::CustomBoard>();
+    app.add_system_set(SystemSet::on_enter(AppState::Menu).with_system(menu::spawn_menu));
+    app.add_system_set(
+        SystemSet::on_update(AppState::Menu)
+            .with_system(menu::menu_buttons)
+            .with_system(menu::edit_custom_field)
+            .with_system(menu::update_menu_texts),
+    );
+    app.add_system_set(SystemSet::on_exit(AppState::Menu).with_system(menu::despawn_menu));
+    app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(hud::spawn_hud));
+    app.add_system_set(SystemSet::on_update(AppState::InGame).with_system(hud::update_hud));
+    app.add_system_set(SystemSet::on_exit(AppState::InGame).with_system(hud::despawn_hud));
+    app.add_system_set(SystemSet::on_enter(AppState::Endless).with_system(hud::spawn_hud));
+    app.add_system_set(SystemSet::on_update(AppState::Endless).with_system(hud::update_endless_hud));
+    app.add_system_set(SystemSet::on_exit(AppState::Endless).with_system(hud::despawn_hud));
+    app.add_system_set(SystemSet::on_enter(AppState::Paused).with_system(pause::spawn_pause_screen));
+    app.add_system_set(SystemSet::on_update(AppState::Paused).with_system(pause::pause_buttons));
+    app.add_system(pause::pause_on_focus_loss);
+    app.add_system_set(SystemSet::on_exit(AppState::Paused).with_system(pause::despawn_pause_screen));
 
-    app.add_startup_system(setup_board);
+    app.add_startup_system(themes::setup_themes);
     app.add_startup_system(camera_setup);
     app.add_system(state_handler);
+    app.add_system(hint_handler);
+    app.add_system(history_handler);
+    app.add_system(stats_screen::toggle_stats_screen);
+    app.add_system(high_scores::prompt_name);
+    app.add_system(high_scores::enter_name);
+    app.add_system(daily::share_daily_result);
+    app.add_system(themes::switch_theme);
+    #[cfg(feature = "audio")]
+    app.add_system(audio::play_sound_cues);
 
     app.run();
 
     println!("Hello, world!");
 }
 
-fn setup_board(
-    mut commands: Commands,
+fn state_handler(
     mut state: ResMut<State<AppState>>,
-    asset_server: Res<AssetServer>,
+    keys: Res<Input<KeyCode>>,
+    typing: Option<Res<Typing>>,
 ) {
-    // Board plugin options
-    commands.insert_resource(BoardOptions {
-        bomb_count: 40,
-        map_size: (20, 20),
-        safe_start: false,
-        tile_padding: 1.,
-        ..Default::default()
-    });
-
-    // Board assets
-    commands.insert_resource(BoardAssets {
-        label: "Default".to_string(),
-        board_material: SpriteMaterial {
-            color: Color::WHITE,
-            ..Default::default()
-        },
-        tile_material: SpriteMaterial {
-            color: Color::DARK_GRAY,
-            ..Default::default()
-        },
-        covered_tile_material: SpriteMaterial {
-            color: Color::GRAY,
-            ..Default::default()
-        },
-        bomb_counter_font: asset_server.load("fonts/pixeled.ttf"),
-        bomb_counter_colors: BoardAssets::default_colors(),
-        flag_material: SpriteMaterial {
-            texture: asset_server.load("sprites/flag.png"),
-            color: Color::WHITE,
-        },
-        bomb_material: SpriteMaterial {
-            texture: asset_server.load("sprites/bomb.png"),
-            color: Color::WHITE,
-        },
-    });
+    // Keys are typed into the name while it's being entered
+    if typing.is_some() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) && state.current() != &AppState::Menu {
+        log::info!("quitting to menu");
+        state.overwrite_replace(AppState::Menu).unwrap();
+    }
+    if keys.just_pressed(KeyCode::Space) {
+        match state.current() {
+            AppState::InGame => state.overwrite_push(AppState::Paused).unwrap(),
+            AppState::Paused => state.overwrite_pop().unwrap(),
+            AppState::Menu | AppState::Endless => (),
+        }
+    }
+    if keys.just_pressed(KeyCode::R) && state.current() == &AppState::InGame {
+        log::info!("restarting game");
+        state.overwrite_restart();
+    }
+}
 
-    // Plugin activation
-    // Workaround for panic when using `state.set()`; see https://github.com/bevyengine/bevy/issues/5552:
-    //   thread 'Compute Task Pool (5)' panicked at 'called `Result::unwrap()` on an `Err` value: StateAlreadyQueued', src/main.rs:96:33
-    //   note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
-    //   thread 'main' panicked at 'called `Option::unwrap()` on a `None` value', /home/brian/.cargo/registry/src/github.com-1ecc6299db9ec823/bevy_tasks-0.9.1/src/task_pool.rs:273:45
-    state.overwrite_set(AppState::InGame).unwrap();
+fn hint_handler(
+    keys: Res<Input<KeyCode>>,
+    typing: Option<Res<Typing>>,
+    mut hint_request_ewr: EventWriter<HintRequestEvent>,
+) {
+    if typing.is_none() && keys.just_pressed(KeyCode::H) {
+        log::debug!("hint requested");
+        hint_request_ewr.send(HintRequestEvent);
+    }
 }
 
-fn state_handler(mut state: ResMut<State<AppState>>, keys: Res<Input<KeyCode>>) {
-    if keys.just_pressed(KeyCode::C) {
-        log::debug!("clearing detected");
-        if state.current() == &AppState::InGame {
-            log::info!("clearing game");
-            state.set(AppState::Out).unwrap();
-        }
+fn history_handler(
+    keys: Res<Input<KeyCode>>,
+    typing: Option<Res<Typing>>,
+    mut undo_ewr: EventWriter<UndoEvent>,
+    mut redo_ewr: EventWriter<RedoEvent>,
+) {
+    if typing.is_some() {
+        return;
     }
-    if keys.just_pressed(KeyCode::G) {
-        log::debug!("loading detected");
-        if state.current() == &AppState::Out {
-            log::info!("loading game");
-            state.set(AppState::InGame).unwrap();
-        }
+    if keys.just_pressed(KeyCode::Z) {
+        log::debug!("undo requested");
+        undo_ewr.send(UndoEvent);
+    }
+    if keys.just_pressed(KeyCode::Y) {
+        log::debug!("redo requested");
+        redo_ewr.send(RedoEvent);
     }
 }
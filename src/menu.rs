@@ -0,0 +1,512 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{
+    AnimationSettings, BoardOptions, DailyChallenge, Difficulty, GameMode, NeighborhoodRule, NumberStyle,
+    SoundSettings, Topology,
+};
+use crate::AppState;
+
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const FOCUSED_COLOR: Color = Color::rgb(0.2, 0.3, 0.5);
+const ERROR_COLOR: Color = Color::rgb(1., 0.4, 0.4);
+
+/// Root node of the main menu
+#[derive(Component)]
+pub struct MenuRoot;
+
+/// Field of the custom board form
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CustomField {
+    Width,
+    Height,
+    Mines,
+}
+
+impl CustomField {
+    const ALL: [Self; 3] = [Self::Width, Self::Height, Self::Mines];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Width => "Width",
+            Self::Height => "Height",
+            Self::Mines => "Mines",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            Self::Width => Self::Height,
+            Self::Height => Self::Mines,
+            Self::Mines => Self::Width,
+        }
+    }
+}
+
+/// Clickable element of the menu
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub enum MenuButton {
+    Preset(Difficulty),
+    Daily,
+    Endless,
+    Field(CustomField),
+    SafeStart,
+    Grid,
+    Wrap,
+    Neighborhood,
+    MultiMine,
+    Lives,
+    Mode,
+    PlayCustom,
+    Palette,
+    Cue,
+    Animations,
+    Sound,
+}
+
+/// Text of the menu reflecting the `CustomBoard` form
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub enum MenuText {
+    Field(CustomField),
+    SafeStart,
+    Grid,
+    Wrap,
+    Neighborhood,
+    MultiMine,
+    Lives,
+    Mode,
+    Error,
+    Palette,
+    Cue,
+    Animations,
+    Sound,
+}
+
+/// State of the custom board form, kept between visits of the menu
+#[derive(Debug, Clone, Resource)]
+pub struct CustomBoard {
+    pub width: String,
+    pub height: String,
+    pub mines: String,
+    pub safe_start: bool,
+    /// Field receiving the typed digits
+    pub focused: CustomField,
+    pub error: Option<String>,
+}
+
+impl Default for CustomBoard {
+    fn default() -> Self {
+        let (width, height) = Difficulty::Intermediate.map_size();
+        Self {
+            width: width.to_string(),
+            height: height.to_string(),
+            mines: Difficulty::Intermediate.bomb_count().to_string(),
+            safe_start: false,
+            focused: CustomField::Width,
+            error: None,
+        }
+    }
+}
+
+impl CustomBoard {
+    /// Longest value of a field
+    const MAX_DIGITS: usize = 4;
+
+    fn field_mut(&mut self, field: CustomField) -> &mut String {
+        match field {
+            CustomField::Width => &mut self.width,
+            CustomField::Height => &mut self.height,
+            CustomField::Mines => &mut self.mines,
+        }
+    }
+
+    fn field(&self, field: CustomField) -> &str {
+        match field {
+            CustomField::Width => &self.width,
+            CustomField::Height => &self.height,
+            CustomField::Mines => &self.mines,
+        }
+    }
+
+    /// Parses the form into `options`, or explains what's wrong with it
+    fn apply(&self, options: &BoardOptions) -> Result<BoardOptions, String> {
+        let parse = |field: CustomField| {
+            self.field(field)
+                .parse::<u16>()
+                .map_err(|_| format!("{} must be a number", field.label().to_lowercase()))
+        };
+        let custom = BoardOptions {
+            map_size: (parse(CustomField::Width)?, parse(CustomField::Height)?),
+            bomb_count: parse(CustomField::Mines)?,
+            safe_start: self.safe_start,
+            layout: None,
+            seed: None,
+            daily: None,
+            ..options.clone()
+        };
+        custom.validate().map_err(|e| e.to_string())?;
+        Ok(custom)
+    }
+}
+
+pub fn spawn_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    custom: Res<CustomBoard>,
+    options: Res<BoardOptions>,
+    number_style: Res<NumberStyle>,
+    animations: Res<AnimationSettings>,
+    sound: Res<SoundSettings>,
+) {
+    let font = asset_server.load("fonts/pixeled.ttf");
+    let style = |size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size: size,
+        color,
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Menu"))
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Minesweeper", style(28., Color::WHITE)));
+            for difficulty in Difficulty::ALL {
+                let (width, height) = difficulty.map_size();
+                let label = format!(
+                    "{} {}x{}, {} mines",
+                    difficulty,
+                    width,
+                    height,
+                    difficulty.bomb_count()
+                );
+                spawn_button(parent, MenuButton::Preset(difficulty), label, None, style(14., Color::WHITE));
+            }
+            let label = format!("Daily challenge {}", DailyChallenge::today().date);
+            spawn_button(parent, MenuButton::Daily, label, None, style(14., Color::WHITE));
+            spawn_button(parent, MenuButton::Endless, "Endless".to_string(), None, style(14., Color::WHITE));
+
+            parent.spawn(TextBundle::from_section("Custom", style(18., Color::WHITE)));
+            for field in CustomField::ALL {
+                let label = format!("{}: {}", field.label(), custom.field(field));
+                spawn_button(parent, MenuButton::Field(field), label, Some(MenuText::Field(field)), style(14., Color::WHITE));
+            }
+            let label = safe_start_label(custom.safe_start);
+            spawn_button(parent, MenuButton::SafeStart, label, Some(MenuText::SafeStart), style(14., Color::WHITE));
+            let label = grid_label(options.topology);
+            spawn_button(parent, MenuButton::Grid, label, Some(MenuText::Grid), style(14., Color::WHITE));
+            let label = wrap_label(options.wrap);
+            spawn_button(parent, MenuButton::Wrap, label, Some(MenuText::Wrap), style(14., Color::WHITE));
+            let label = neighborhood_label(options.neighborhood);
+            spawn_button(parent, MenuButton::Neighborhood, label, Some(MenuText::Neighborhood), style(14., Color::WHITE));
+            let label = multi_mine_label(options.multi_mine);
+            spawn_button(parent, MenuButton::MultiMine, label, Some(MenuText::MultiMine), style(14., Color::WHITE));
+            let label = lives_label(options.lives);
+            spawn_button(parent, MenuButton::Lives, label, Some(MenuText::Lives), style(14., Color::WHITE));
+            let label = mode_label(options.mode);
+            spawn_button(parent, MenuButton::Mode, label, Some(MenuText::Mode), style(14., Color::WHITE));
+            spawn_button(parent, MenuButton::PlayCustom, "Play custom".to_string(), None, style(14., Color::WHITE));
+            parent
+                .spawn(TextBundle::from_section(
+                    custom.error.clone().unwrap_or_default(),
+                    style(12., ERROR_COLOR),
+                ))
+                .insert(MenuText::Error);
+
+            parent.spawn(TextBundle::from_section("Settings", style(18., Color::WHITE)));
+            let label = palette_label(&number_style);
+            spawn_button(parent, MenuButton::Palette, label, Some(MenuText::Palette), style(14., Color::WHITE));
+            let label = cue_label(&number_style);
+            spawn_button(parent, MenuButton::Cue, label, Some(MenuText::Cue), style(14., Color::WHITE));
+            let label = animations_label(&animations);
+            spawn_button(parent, MenuButton::Animations, label, Some(MenuText::Animations), style(14., Color::WHITE));
+            let label = sound_label(&sound);
+            spawn_button(parent, MenuButton::Sound, label, Some(MenuText::Sound), style(14., Color::WHITE));
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: MenuButton,
+    label: String,
+    text: Option<MenuText>,
+    style: TextStyle,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(360.), Val::Px(28.)),
+                margin: UiRect::all(Val::Px(1.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..Default::default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            let mut label = parent.spawn(TextBundle::from_section(label, style));
+            if let Some(text) = text {
+                label.insert(text);
+            }
+        });
+}
+
+fn safe_start_label(safe_start: bool) -> String {
+    format!("Safe start: {}", if safe_start { "on" } else { "off" })
+}
+
+fn grid_label(topology: Topology) -> String {
+    format!("Grid: {}", topology)
+}
+
+fn wrap_label(wrap: bool) -> String {
+    format!("Edges: {}", if wrap { "wrap around" } else { "bounded" })
+}
+
+fn neighborhood_label(rule: NeighborhoodRule) -> String {
+    format!("Neighbors: {}", rule)
+}
+
+fn multi_mine_label(multi_mine: bool) -> String {
+    format!("Mines per tile: {}", if multi_mine { "1 to 3" } else { "1" })
+}
+
+fn lives_label(lives: Option<u8>) -> String {
+    match lives {
+        None => "Lives: off".to_string(),
+        Some(lives) => format!("Lives: {}", lives),
+    }
+}
+
+/// Cycles through the lives modes offered by the menu, then back to the standard game
+fn next_lives(lives: Option<u8>) -> Option<u8> {
+    const CHOICES: [Option<u8>; 4] = [None, Some(2), Some(3), Some(5)];
+    let index = CHOICES.iter().position(|l| *l == lives).unwrap_or_default();
+    CHOICES[(index + 1) % CHOICES.len()]
+}
+
+fn mode_label(mode: GameMode) -> String {
+    format!("Mode: {}", mode)
+}
+
+fn palette_label(number_style: &NumberStyle) -> String {
+    format!("Number colors: {}", number_style.palette)
+}
+
+fn cue_label(number_style: &NumberStyle) -> String {
+    format!("Number cue: {}", number_style.cue)
+}
+
+fn animations_label(animations: &AnimationSettings) -> String {
+    format!("Animations: {}", if animations.enabled { "on" } else { "off" })
+}
+
+fn sound_label(sound: &SoundSettings) -> String {
+    if sound.muted {
+        "Sound: off".to_string()
+    } else {
+        format!("Sound: {}%", (sound.volume * 100.).round())
+    }
+}
+
+/// Steps the volume by a quarter, muting after the loudest
+fn next_volume(sound: &mut SoundSettings) {
+    if sound.muted {
+        sound.muted = false;
+        sound.volume = 0.25;
+    } else if sound.volume >= 1. {
+        sound.muted = true;
+    } else {
+        sound.volume = (sound.volume + 0.25).min(1.);
+    }
+}
+
+/// Starts a game from the clicked preset or the custom form
+#[allow(clippy::too_many_arguments)]
+pub fn menu_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut options: ResMut<BoardOptions>,
+    mut custom: ResMut<CustomBoard>,
+    mut number_style: ResMut<NumberStyle>,
+    mut animations: ResMut<AnimationSettings>,
+    mut sound: ResMut<SoundSettings>,
+    keys: Res<Input<KeyCode>>,
+    clicked: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut buttons: Query<(&Interaction, &MenuButton, &mut BackgroundColor)>,
+) {
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        *color = match interaction {
+            Interaction::Clicked | Interaction::Hovered => HOVERED_COLOR,
+            Interaction::None if *button == MenuButton::Field(custom.focused) => FOCUSED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+    // Only the frame a button gets clicked counts, not while it's held
+    let mut pressed = clicked
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, button)| *button);
+    if keys.just_pressed(KeyCode::Return) {
+        pressed = Some(MenuButton::PlayCustom);
+    }
+
+    let new_options = match pressed {
+        None => return,
+        Some(MenuButton::Field(field)) => {
+            custom.focused = field;
+            return;
+        }
+        Some(MenuButton::SafeStart) => {
+            custom.safe_start = !custom.safe_start;
+            return;
+        }
+        Some(MenuButton::Grid) => {
+            options.topology = options.topology.next();
+            return;
+        }
+        Some(MenuButton::Wrap) => {
+            options.wrap = !options.wrap;
+            return;
+        }
+        Some(MenuButton::Neighborhood) => {
+            options.neighborhood = options.neighborhood.next();
+            return;
+        }
+        Some(MenuButton::MultiMine) => {
+            options.multi_mine = !options.multi_mine;
+            return;
+        }
+        Some(MenuButton::Lives) => {
+            options.lives = next_lives(options.lives);
+            return;
+        }
+        Some(MenuButton::Mode) => {
+            options.mode = options.mode.next();
+            return;
+        }
+        Some(MenuButton::Palette) => {
+            number_style.palette = number_style.palette.next();
+            return;
+        }
+        Some(MenuButton::Cue) => {
+            number_style.cue = number_style.cue.next();
+            return;
+        }
+        Some(MenuButton::Animations) => {
+            animations.enabled = !animations.enabled;
+            return;
+        }
+        Some(MenuButton::Sound) => {
+            next_volume(&mut sound);
+            return;
+        }
+        Some(MenuButton::Endless) => {
+            log::info!("starting endless game");
+            state.overwrite_set(AppState::Endless).unwrap();
+            return;
+        }
+        Some(MenuButton::Preset(difficulty)) => BoardOptions {
+            map_size: difficulty.map_size(),
+            bomb_count: difficulty.bomb_count(),
+            safe_start: custom.safe_start,
+            layout: None,
+            seed: None,
+            daily: None,
+            ..options.clone()
+        },
+        Some(MenuButton::Daily) => DailyChallenge::today().options(&options),
+        Some(MenuButton::PlayCustom) => match custom.apply(&options) {
+            Ok(o) => o,
+            Err(e) => {
+                custom.error = Some(e);
+                return;
+            }
+        },
+    };
+    custom.error = None;
+    log::info!(
+        "starting {}x{} board with {} mines",
+        new_options.map_size.0,
+        new_options.map_size.1,
+        new_options.bomb_count
+    );
+    // The options are in place before `create_board` runs on entering the game
+    *options = new_options;
+    state.overwrite_set(AppState::InGame).unwrap();
+}
+
+/// Types digits in the focused custom field, `Tab` moves to the next one
+pub fn edit_custom_field(
+    keys: Res<Input<KeyCode>>,
+    mut custom: ResMut<CustomBoard>,
+    mut received_character_evr: EventReader<ReceivedCharacter>,
+) {
+    let typed: Vec<char> = received_character_evr.iter().map(|e| e.char).collect();
+    if keys.just_pressed(KeyCode::Tab) {
+        custom.focused = custom.focused.next();
+    }
+    let focused = custom.focused;
+    if keys.just_pressed(KeyCode::Back) {
+        custom.field_mut(focused).pop();
+    }
+    for c in typed.into_iter().filter(char::is_ascii_digit) {
+        let value = custom.field_mut(focused);
+        if value.len() < CustomBoard::MAX_DIGITS {
+            value.push(c);
+        }
+    }
+}
+
+/// Refreshes the menu texts when the form changes
+pub fn update_menu_texts(
+    custom: Res<CustomBoard>,
+    options: Res<BoardOptions>,
+    number_style: Res<NumberStyle>,
+    animations: Res<AnimationSettings>,
+    sound: Res<SoundSettings>,
+    mut texts: Query<(&mut Text, &MenuText)>,
+) {
+    let changed = custom.is_changed()
+        || options.is_changed()
+        || number_style.is_changed()
+        || animations.is_changed()
+        || sound.is_changed();
+    if !changed {
+        return;
+    }
+    for (mut text, kind) in texts.iter_mut() {
+        text.sections[0].value = match kind {
+            MenuText::Field(field) => format!("{}: {}", field.label(), custom.field(*field)),
+            MenuText::SafeStart => safe_start_label(custom.safe_start),
+            MenuText::Grid => grid_label(options.topology),
+            MenuText::Wrap => wrap_label(options.wrap),
+            MenuText::Neighborhood => neighborhood_label(options.neighborhood),
+            MenuText::MultiMine => multi_mine_label(options.multi_mine),
+            MenuText::Lives => lives_label(options.lives),
+            MenuText::Mode => mode_label(options.mode),
+            MenuText::Error => custom.error.clone().unwrap_or_default(),
+            MenuText::Palette => palette_label(&number_style),
+            MenuText::Cue => cue_label(&number_style),
+            MenuText::Animations => animations_label(&animations),
+            MenuText::Sound => sound_label(&sound),
+        };
+    }
+}
+
+pub fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<MenuRoot>>) {
+    for menu in menus.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::log;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use crate::AppState;
+
+/// Root node of the pause screen
+#[derive(Component)]
+pub struct PauseScreen;
+
+/// Clickable element of the pause screen
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Component)]
+pub enum PauseButton {
+    Resume,
+    QuitToMenu,
+}
+
+pub fn spawn_pause_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/pixeled.ttf"),
+        font_size: 14.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            z_index: ZIndex::Global(5),
+            ..Default::default()
+        })
+        .insert(Name::new("Pause screen"))
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Paused", TextStyle {
+                font_size: 28.,
+                ..style.clone()
+            }));
+            for (button, label) in [
+                (PauseButton::Resume, "Resume"),
+                (PauseButton::QuitToMenu, "Quit to menu"),
+            ] {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(240.), Val::Px(40.)),
+                            margin: UiRect::all(Val::Px(4.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, style.clone()));
+                    });
+            }
+        });
+}
+
+pub fn pause_buttons(
+    mut state: ResMut<State<AppState>>,
+    buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            PauseButton::Resume => state.overwrite_pop().unwrap(),
+            // Unwinds the paused game so the board gets cleaned up
+            PauseButton::QuitToMenu => state.overwrite_replace(AppState::Menu).unwrap(),
+        }
+        return;
+    }
+}
+
+/// Pauses the game when the window loses focus, resuming is left to the player
+pub fn pause_on_focus_loss(
+    mut state: ResMut<State<AppState>>,
+    mut window_focused_evr: EventReader<WindowFocused>,
+) {
+    if window_focused_evr.iter().any(|e| !e.focused) && state.current() == &AppState::InGame {
+        log::info!("window lost focus, pausing");
+        state.overwrite_push(AppState::Paused).unwrap();
+    }
+}
+
+pub fn despawn_pause_screen(mut commands: Commands, screens: Query<Entity, With<PauseScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
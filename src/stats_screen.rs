@@ -0,0 +1,81 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::date::Date;
+use board_plugin::resources::{DailyHistory, HighScores, Stats, Typing};
+
+/// Root node of the stats screen
+#[derive(Component)]
+pub struct StatsScreen;
+
+/// Loads the stats from the user's data directory, in memory only if that fails
+pub fn load_stats() -> Stats {
+    let path = match Stats::default_path() {
+        Some(p) => p,
+        None => {
+            log::warn!("No data directory, stats won't be saved");
+            return Stats::default();
+        }
+    };
+    match Stats::load(&path) {
+        Ok(stats) => stats,
+        Err(e) => {
+            log::error!("Failed to load stats from {}: {}", path.display(), e);
+            Stats::default()
+        }
+    }
+}
+
+/// Shows or hides the stats and high scores screen with `S`
+#[allow(clippy::too_many_arguments)]
+pub fn toggle_stats_screen(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    stats: Res<Stats>,
+    high_scores: Res<HighScores>,
+    daily: Res<DailyHistory>,
+    typing: Option<Res<Typing>>,
+    asset_server: Res<AssetServer>,
+    screens: Query<Entity, With<StatsScreen>>,
+) {
+    if typing.is_some() || !keys.just_pressed(KeyCode::S) {
+        return;
+    }
+    if let Ok(screen) = screens.get_single() {
+        commands.entity(screen).despawn_recursive();
+        return;
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            z_index: ZIndex::Global(10),
+            ..Default::default()
+        })
+        .insert(Name::new("Stats screen"))
+        .insert(StatsScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("{}\n\n{}\n\n{}", *stats, daily_summary(&daily), *high_scores),
+                TextStyle {
+                    font: asset_server.load("fonts/pixeled.ttf"),
+                    font_size: 14.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn daily_summary(daily: &DailyHistory) -> String {
+    format!(
+        "Daily challenges: {} played, streak {} (best {})",
+        daily.results.len(),
+        daily.current_streak(Date::today()),
+        daily.best_streak()
+    )
+}
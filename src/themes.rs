@@ -0,0 +1,57 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::resources::{BoardAssets, Typing};
+
+/// Theme files shipped in `assets/themes`, the first one is used at startup
+const THEME_PATHS: [&str; 3] = [
+    "themes/classic.theme.json",
+    "themes/dark.theme.json",
+    "themes/high_contrast.theme.json",
+];
+
+/// Loaded themes and the one in use
+#[derive(Debug, Resource)]
+pub struct Themes {
+    pub handles: Vec<Handle<BoardAssets>>,
+    pub current: usize,
+}
+
+/// Inserts the classic board assets right away and starts loading the themes
+pub fn setup_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BoardAssets::classic(&asset_server));
+    commands.insert_resource(Themes {
+        handles: THEME_PATHS.iter().map(|p| asset_server.load(*p)).collect(),
+        current: 0,
+    });
+}
+
+/// Cycles through the themes with `T`, and applies the current one once loaded or edited
+pub fn switch_theme(
+    keys: Res<Input<KeyCode>>,
+    typing: Option<Res<Typing>>,
+    mut themes: ResMut<Themes>,
+    theme_assets: Res<Assets<BoardAssets>>,
+    mut board_assets: ResMut<BoardAssets>,
+    mut asset_evr: EventReader<AssetEvent<BoardAssets>>,
+) {
+    let mut changed = asset_evr.iter().any(|e| match e {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == themes.handles[themes.current]
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if typing.is_none() && keys.just_pressed(KeyCode::T) {
+        themes.current = (themes.current + 1) % themes.handles.len();
+        changed = true;
+    }
+    if !changed {
+        return;
+    }
+    match theme_assets.get(&themes.handles[themes.current]) {
+        Some(theme) => {
+            log::info!("Using the {} theme", theme.label);
+            *board_assets = theme.clone();
+        }
+        None => log::warn!("Theme {} isn't loaded", THEME_PATHS[themes.current]),
+    }
+}